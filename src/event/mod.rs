@@ -4,7 +4,20 @@
 mod event;
 mod events;
 mod source;
+mod tracker;
+
+cfg_os_poll! {
+    pub(crate) mod record;
+    pub use self::record::Replay;
+
+    #[cfg(unix)]
+    mod readiness;
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub use self::readiness::Readiness;
+}
 
 pub use self::event::Event;
 pub use self::events::{Events, Iter};
 pub use self::source::Source;
+pub use self::tracker::ReadinessTracker;
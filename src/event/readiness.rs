@@ -0,0 +1,93 @@
+use std::fmt;
+
+const READABLE: u8 = 1 << 0;
+const WRITABLE: u8 = 1 << 1;
+const ERROR: u8 = 1 << 2;
+const READ_CLOSED: u8 = 1 << 3;
+const WRITE_CLOSED: u8 = 1 << 4;
+const PRIORITY: u8 = 1 << 5;
+
+/// Current readiness of an event source, as returned by [`Registry::probe`].
+///
+/// Unlike an [`Event`] this is the readiness at the moment the source was
+/// probed, not a change in readiness. The methods match the methods with the
+/// same name on [`Event`].
+///
+/// [`Registry::probe`]: crate::Registry::probe
+/// [`Event`]: crate::event::Event
+#[derive(Copy, Clone, Eq, PartialEq, Default)]
+pub struct Readiness(u8);
+
+impl Readiness {
+    /// Returns true if the source is readable.
+    pub fn is_readable(self) -> bool {
+        self.0 & READABLE != 0
+    }
+
+    /// Returns true if the source is writable.
+    pub fn is_writable(self) -> bool {
+        self.0 & WRITABLE != 0
+    }
+
+    /// Returns true if the source is in an error state.
+    pub fn is_error(self) -> bool {
+        self.0 & ERROR != 0
+    }
+
+    /// Returns true if the read side of the source is closed.
+    pub fn is_read_closed(self) -> bool {
+        self.0 & READ_CLOSED != 0
+    }
+
+    /// Returns true if the write side of the source is closed.
+    pub fn is_write_closed(self) -> bool {
+        self.0 & WRITE_CLOSED != 0
+    }
+
+    /// Returns true if the source has priority data available.
+    pub fn is_priority(self) -> bool {
+        self.0 & PRIORITY != 0
+    }
+
+    /// Returns true if the source isn't ready in any way.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub(crate) fn set_readable(&mut self) {
+        self.0 |= READABLE;
+    }
+
+    pub(crate) fn set_writable(&mut self) {
+        self.0 |= WRITABLE;
+    }
+
+    pub(crate) fn set_error(&mut self) {
+        self.0 |= ERROR;
+    }
+
+    pub(crate) fn set_read_closed(&mut self) {
+        self.0 |= READ_CLOSED;
+    }
+
+    pub(crate) fn set_write_closed(&mut self) {
+        self.0 |= WRITE_CLOSED;
+    }
+
+    pub(crate) fn set_priority(&mut self) {
+        self.0 |= PRIORITY;
+    }
+}
+
+impl fmt::Debug for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Readiness")
+            .field("readable", &self.is_readable())
+            .field("writable", &self.is_writable())
+            .field("error", &self.is_error())
+            .field("read_closed", &self.is_read_closed())
+            .field("write_closed", &self.is_write_closed())
+            .field("priority", &self.is_priority())
+            .finish()
+    }
+}
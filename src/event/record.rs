@@ -0,0 +1,246 @@
+//! Recording and replaying of events, see [`Poll::record`] and [`Replay`].
+//!
+//! [`Poll::record`]: crate::Poll::record
+
+use crate::event::Event;
+use crate::{sys, Events, Token};
+
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::{cmp, fmt};
+
+const HEADER: [u8; 8] = *b"mio-rec\x01";
+
+// Readiness bits.
+pub(crate) const READABLE: u8 = 1 << 0;
+pub(crate) const WRITABLE: u8 = 1 << 1;
+pub(crate) const ERROR: u8 = 1 << 2;
+pub(crate) const READ_CLOSED: u8 = 1 << 3;
+pub(crate) const WRITE_CLOSED: u8 = 1 << 4;
+pub(crate) const PRIORITY: u8 = 1 << 5;
+pub(crate) const AIO: u8 = 1 << 6;
+pub(crate) const LIO: u8 = 1 << 7;
+
+/// Records the events returned by `Poll::poll`.
+pub(crate) struct Recorder {
+    /// `Mutex` only to keep `Poll` `Sync`, it's only accessed mutably.
+    writer: Mutex<Box<dyn Write + Send>>,
+    start: Instant,
+    /// Reused buffer to write a batch in a single call.
+    buf: Vec<u8>,
+}
+
+impl Recorder {
+    pub(crate) fn new(mut writer: Box<dyn Write + Send>) -> io::Result<Recorder> {
+        writer.write_all(&HEADER)?;
+        Ok(Recorder {
+            writer: Mutex::new(writer),
+            start: Instant::now(),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Write a batch of `events` to the recording.
+    pub(crate) fn record(&mut self, events: &Events) -> io::Result<()> {
+        let elapsed = cmp::min(self.start.elapsed().as_micros(), u128::from(u64::MAX)) as u64;
+        self.buf.clear();
+        self.buf.extend_from_slice(&elapsed.to_le_bytes());
+        self.buf
+            .extend_from_slice(&(events.iter().count() as u32).to_le_bytes());
+        for event in events.iter() {
+            self.buf
+                .extend_from_slice(&(event.token().0 as u64).to_le_bytes());
+            self.buf.push(readiness(event));
+        }
+
+        let writer = self.writer.get_mut().unwrap();
+        writer.write_all(&self.buf)?;
+        writer.flush()
+    }
+
+    pub(crate) fn into_inner(self) -> Box<dyn Write + Send> {
+        self.writer.into_inner().unwrap()
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+fn readiness(event: &Event) -> u8 {
+    let mut readiness = 0;
+    let checks: [(bool, u8); 8] = [
+        (event.is_readable(), READABLE),
+        (event.is_writable(), WRITABLE),
+        (event.is_error(), ERROR),
+        (event.is_read_closed(), READ_CLOSED),
+        (event.is_write_closed(), WRITE_CLOSED),
+        (event.is_priority(), PRIORITY),
+        (event.is_aio(), AIO),
+        (event.is_lio(), LIO),
+    ];
+    for &(is_set, bit) in checks.iter() {
+        if is_set {
+            readiness |= bit;
+        }
+    }
+    readiness
+}
+
+/// Replays events recorded using [`Poll::record`].
+///
+/// Each call to [`poll`] fills [`Events`] with the next batch of recorded
+/// events, as returned by a single call to [`Poll::poll`], without blocking.
+/// This allows event handling logic to be run again, deterministically,
+/// against a recording.
+///
+/// [`Poll::record`]: crate::Poll::record
+/// [`Poll::poll`]: crate::Poll::poll
+/// [`poll`]: Replay::poll
+///
+/// # Format
+///
+/// A recording starts with the 8 byte header `b"mio-rec\x01"`, the last byte
+/// being the version of the format. It's followed by a batch of events for
+/// each call to `Poll::poll`. A batch starts with the time elapsed since the
+/// recording started in microseconds (`u64`) and the number of events in the
+/// batch (`u32`), followed by the events. Each event is the token (`u64`)
+/// followed by the readiness bits (`u8`): readable, writable, error, read
+/// closed, write closed, priority, AIO and LIO, starting at the least
+/// significant bit. All integers are little-endian.
+///
+/// # Notes
+///
+/// The readiness of an event is recorded as returned by the methods on
+/// [`Event`]. Not every combination of readiness can be represented on every
+/// platform, e.g. on platforms that use kqueue an event is either readable or
+/// writable, so an event can be replayed as multiple events or with slightly
+/// different readiness, especially when replaying a recording made on another
+/// platform.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::event::Replay;
+/// use mio::{Events, Poll, Token, Waker};
+/// use std::fs::File;
+/// use std::time::Duration;
+///
+/// let path = std::env::temp_dir().join("mio-replay-example");
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+/// let waker = Waker::new(poll.registry(), Token(10))?;
+///
+/// poll.record(File::create(&path)?)?;
+/// waker.wake()?;
+/// poll.poll(&mut events, Some(Duration::from_millis(100)))?;
+/// poll.stop_recording();
+///
+/// let mut replay = Replay::new(File::open(&path)?)?;
+/// while let Some(_elapsed) = replay.poll(&mut events)? {
+///     for event in events.iter() {
+///         assert_eq!(event.token(), Token(10));
+///         assert!(event.is_readable());
+///     }
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Replay<R> {
+    reader: R,
+}
+
+impl<R: Read> Replay<R> {
+    /// Create a new `Replay` reading the recording from `reader`.
+    ///
+    /// Returns an error if `reader` doesn't contain a recording.
+    pub fn new(mut reader: R) -> io::Result<Replay<R>> {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        if header != HEADER {
+            return Err(invalid_data("invalid recording header"));
+        }
+        Ok(Replay { reader })
+    }
+
+    /// Fill `events` with the next batch of recorded events.
+    ///
+    /// Returns the time between the start of the recording and when the batch
+    /// was recorded, or `None` at the end of the recording.
+    ///
+    /// Returns an error of kind `InvalidInput` if `events` can't hold all
+    /// events in the batch, the batch is skipped in that case.
+    pub fn poll(&mut self, events: &mut Events) -> io::Result<Option<Duration>> {
+        events.clear();
+
+        let mut header = [0; 12];
+        // Distinguish between the end of the recording and a truncated batch.
+        loop {
+            match self.reader.read(&mut header) {
+                Ok(0) => return Ok(None),
+                Ok(n) => {
+                    self.reader.read_exact(&mut header[n..])?;
+                    break;
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        let elapsed = Duration::from_micros(u64_from_le(&header[..8]));
+        let count = u32_from_le(&header[8..]) as usize;
+
+        // A recorded event can map to multiple system events, e.g. on kqueue
+        // readable and writable readiness are separate events.
+        let mut pushed = 0;
+        let mut event = [0; 9];
+        for _ in 0..count {
+            self.reader.read_exact(&mut event)?;
+            let token = Token(u64_from_le(&event[..8]) as usize);
+            let readiness = event[8];
+            pushed += sys::event::count(readiness);
+            // Keep reading if `events` is full, so the next call starts at
+            // the next batch.
+            if pushed <= events.capacity() {
+                sys::event::push(events.sys(), token, readiness);
+            }
+        }
+        if pushed > events.capacity() {
+            events.clear();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`Events` capacity too small for recorded batch",
+            ));
+        }
+        Ok(Some(elapsed))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn u64_from_le(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(bytes);
+    u64::from_le_bytes(buf)
+}
+
+fn u32_from_le(bytes: &[u8]) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(bytes);
+    u32::from_le_bytes(buf)
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,156 @@
+use crate::event::Event;
+
+use std::io;
+
+/// Tracks the readiness of a single edge-triggered event source.
+///
+/// Events returned by [`Poll::poll`] are edge-triggered: an event is only
+/// returned once the readiness of a source changes. After an event a source
+/// remains ready until an operation returns a [`WouldBlock`] error, which means
+/// the application has to remember whether or not a source is still ready.
+/// `ReadinessTracker` does this bookkeeping: readiness is set by [`update`],
+/// using the events returned by `Poll::poll`, and cleared by [`do_read`] and
+/// [`do_write`] once the operation returns a `WouldBlock` error. Checking the
+/// readiness doesn't involve any system calls.
+///
+/// [`Poll::poll`]: crate::Poll::poll
+/// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+/// [`update`]: ReadinessTracker::update
+/// [`do_read`]: ReadinessTracker::do_read
+/// [`do_write`]: ReadinessTracker::do_write
+///
+/// # Examples
+///
+#[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+#[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+/// # use std::error::Error;
+/// # use std::net;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::event::ReadinessTracker;
+/// use mio::net::TcpStream;
+/// use mio::{Events, Interest, Poll, Token};
+/// use std::io::Read;
+/// use std::time::Duration;
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+///
+/// let listener = net::TcpListener::bind("127.0.0.1:0")?;
+/// let mut stream = TcpStream::connect(listener.local_addr()?)?;
+/// poll.registry().register(&mut stream, Token(0), Interest::READABLE)?;
+/// let mut tracker = ReadinessTracker::new();
+///
+/// poll.poll(&mut events, Some(Duration::from_millis(100)))?;
+/// for event in events.iter() {
+///     tracker.update(event);
+/// }
+///
+/// let mut buf = [0; 4096];
+/// while tracker.is_readable() {
+///     match tracker.do_read(|| stream.read(&mut buf)) {
+///         Ok(0) => break, // Connection closed.
+///         Ok(n) => println!("read {} bytes", n),
+///         // Readiness is cleared, wait for the next event.
+///         Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
+///         Err(err) => return Err(err.into()),
+///     }
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadinessTracker {
+    readable: bool,
+    writable: bool,
+}
+
+impl ReadinessTracker {
+    /// Create a new `ReadinessTracker`, the source is assumed to be not ready.
+    pub fn new() -> ReadinessTracker {
+        ReadinessTracker::default()
+    }
+
+    /// Update the readiness using `event`.
+    ///
+    /// A closed or error event marks the source as readable and writable,
+    /// because the next operation will return without blocking (returning the
+    /// error or end of stream). The caller must ensure that `event` belongs to
+    /// the tracked source, e.g. by checking the event's token.
+    pub fn update(&mut self, event: &Event) {
+        if event.is_readable() || event.is_read_closed() || event.is_error() {
+            self.readable = true;
+        }
+        if event.is_writable() || event.is_write_closed() || event.is_error() {
+            self.writable = true;
+        }
+    }
+
+    /// Returns true if the source is (still) readable, i.e. if a read
+    /// operation is worth trying.
+    pub fn is_readable(&self) -> bool {
+        self.readable
+    }
+
+    /// Returns true if the source is (still) writable, i.e. if a write
+    /// operation is worth trying.
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    /// Mark the source as readable.
+    pub fn set_readable(&mut self) {
+        self.readable = true;
+    }
+
+    /// Mark the source as writable.
+    pub fn set_writable(&mut self) {
+        self.writable = true;
+    }
+
+    /// Mark the source as not readable.
+    pub fn clear_readable(&mut self) {
+        self.readable = false;
+    }
+
+    /// Mark the source as not writable.
+    pub fn clear_writable(&mut self) {
+        self.writable = false;
+    }
+
+    /// Execute a read operation `f`, marking the source as not readable if it
+    /// returns a [`WouldBlock`] error.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    pub fn do_read<F, T>(&mut self, f: F) -> io::Result<T>
+    where
+        F: FnOnce() -> io::Result<T>,
+    {
+        let result = f();
+        if is_would_block(&result) {
+            self.readable = false;
+        }
+        result
+    }
+
+    /// Execute a write operation `f`, marking the source as not writable if it
+    /// returns a [`WouldBlock`] error.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    pub fn do_write<F, T>(&mut self, f: F) -> io::Result<T>
+    where
+        F: FnOnce() -> io::Result<T>,
+    {
+        let result = f();
+        if is_would_block(&result) {
+            self.writable = false;
+        }
+        result
+    }
+}
+
+fn is_would_block<T>(result: &io::Result<T>) -> bool {
+    match result {
+        Err(err) => err.kind() == io::ErrorKind::WouldBlock,
+        Ok(_) => false,
+    }
+}
@@ -0,0 +1,385 @@
+//! A reference event loop driver.
+//!
+//! Mio leaves the structure of the event loop to the user, see the [`guide`].
+//! This module provides a small, ready made event loop on top of [`Poll`] for
+//! applications that don't need anything more elaborate. The [`EventLoop`]
+//! type polls for readiness events and dispatches them to a [`Handler`], runs
+//! timeouts set using [`EventLoop::timeout`] and delivers messages sent from
+//! other threads using a [`Sender`].
+//!
+//! [`guide`]: crate::guide
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! use mio::event::Event;
+//! use mio::event_loop::{EventLoop, Handler};
+//! use std::thread;
+//! use std::time::Duration;
+//!
+//! struct Counter {
+//!     count: usize,
+//! }
+//!
+//! impl Handler for Counter {
+//!     type Timeout = &'static str;
+//!     type Message = usize;
+//!
+//!     fn ready(&mut self, _: &mut EventLoop<Self>, _: &Event) {
+//!         // Handle readiness events for registered event sources.
+//!     }
+//!
+//!     fn notify(&mut self, _: &mut EventLoop<Self>, n: usize) {
+//!         self.count += n;
+//!     }
+//!
+//!     fn timeout(&mut self, event_loop: &mut EventLoop<Self>, msg: &'static str) {
+//!         assert_eq!(msg, "stop");
+//!         event_loop.shutdown();
+//!     }
+//! }
+//!
+//! let mut event_loop = EventLoop::new()?;
+//!
+//! let sender = event_loop.channel();
+//! thread::spawn(move || sender.send(10).unwrap());
+//!
+//! event_loop.timeout("stop", Duration::from_millis(100));
+//!
+//! let mut handler = Counter { count: 0 };
+//! event_loop.run(&mut handler)?;
+//! assert_eq!(handler.count, 10);
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::event::Event;
+use crate::{Events, Poll, Registry, Token, Waker};
+
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use std::{error, fmt, io, mem};
+
+/// Token used by the [`Waker`] of the [`EventLoop`].
+///
+/// Events with this token are never passed to [`Handler::ready`].
+pub const NOTIFY: Token = Token(usize::MAX);
+
+/// Maximum number of messages delivered to [`Handler::notify`] per iteration
+/// of the event loop, to ensure I/O and timeouts are not starved.
+const MESSAGES_PER_TICK: usize = 256;
+
+/// Default capacity of the [`Events`] used by the event loop.
+const EVENTS_CAPACITY: usize = 1024;
+
+/// Handles the events of an [`EventLoop`].
+///
+/// All methods have a default implementation that does nothing.
+pub trait Handler: Sized {
+    /// Value passed to [`EventLoop::timeout`] and returned to
+    /// [`Handler::timeout`] when the timeout expires.
+    type Timeout;
+    /// Message sent using a [`Sender`] and delivered to [`Handler::notify`].
+    type Message: Send;
+
+    /// Called for every readiness event received for a registered
+    /// [`event::Source`].
+    ///
+    /// [`event::Source`]: crate::event::Source
+    fn ready(&mut self, event_loop: &mut EventLoop<Self>, event: &Event) {
+        let _ = (event_loop, event);
+    }
+
+    /// Called for every message sent using a [`Sender`].
+    fn notify(&mut self, event_loop: &mut EventLoop<Self>, msg: Self::Message) {
+        let _ = (event_loop, msg);
+    }
+
+    /// Called when a timeout set using [`EventLoop::timeout`] expires.
+    fn timeout(&mut self, event_loop: &mut EventLoop<Self>, timeout: Self::Timeout) {
+        let _ = (event_loop, timeout);
+    }
+
+    /// Called at the end of every iteration of the event loop, after all
+    /// events, messages and timeouts are handled.
+    fn tick(&mut self, event_loop: &mut EventLoop<Self>) {
+        let _ = event_loop;
+    }
+}
+
+/// Handle to a timeout set using [`EventLoop::timeout`], used to cancel it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Timeout(u64);
+
+/// A single threaded event loop, see the [module documentation].
+///
+/// [module documentation]: crate::event_loop
+///
+/// # Notes
+///
+/// The event loop registers a [`Waker`] with the [`NOTIFY`] token with its
+/// `Poll` instance, event sources must be registered using different tokens.
+/// As only a single `Waker` can be active per `Poll` no other `Waker` can be
+/// created for the event loop's [`Registry`], use a [`Sender`] instead.
+pub struct EventLoop<H: Handler> {
+    running: bool,
+    poll: Poll,
+    events: Events,
+    /// Deadlines of the timeouts, ordered with the earliest deadline first.
+    deadlines: BinaryHeap<Reverse<(Instant, u64)>>,
+    /// Pending (not cancelled) timeouts.
+    timeouts: HashMap<u64, H::Timeout>,
+    next_timeout_id: u64,
+    waker: Arc<Waker>,
+    sender: mpsc::Sender<H::Message>,
+    receiver: mpsc::Receiver<H::Message>,
+}
+
+impl<H: Handler> EventLoop<H> {
+    /// Create a new `EventLoop`.
+    pub fn new() -> io::Result<EventLoop<H>> {
+        EventLoop::with_events_capacity(EVENTS_CAPACITY)
+    }
+
+    /// Create a new `EventLoop` that handles at most `capacity` readiness
+    /// events per iteration.
+    pub fn with_events_capacity(capacity: usize) -> io::Result<EventLoop<H>> {
+        let poll = Poll::new()?;
+        let waker = Waker::new(poll.registry(), NOTIFY)?;
+        let (sender, receiver) = mpsc::channel();
+        Ok(EventLoop {
+            running: false,
+            poll,
+            events: Events::with_capacity(capacity),
+            deadlines: BinaryHeap::new(),
+            timeouts: HashMap::new(),
+            next_timeout_id: 0,
+            waker: Arc::new(waker),
+            sender,
+            receiver,
+        })
+    }
+
+    /// Returns the `Registry` used to register event sources with the event
+    /// loop.
+    pub fn registry(&self) -> &Registry {
+        self.poll.registry()
+    }
+
+    /// Returns a [`Sender`] to send messages to the event loop, possibly from
+    /// other threads.
+    pub fn channel(&self) -> Sender<H::Message> {
+        Sender {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+
+    /// Call [`Handler::timeout`] with `timeout` once `delay` has elapsed.
+    ///
+    /// The timeout is checked once per iteration of the event loop, so it may
+    /// fire late if handling events takes a long time.
+    pub fn timeout(&mut self, timeout: H::Timeout, delay: Duration) -> Timeout {
+        let id = self.next_timeout_id;
+        self.next_timeout_id += 1;
+        self.deadlines.push(Reverse((Instant::now() + delay, id)));
+        self.timeouts.insert(id, timeout);
+        Timeout(id)
+    }
+
+    /// Cancel a timeout, returning `true` if the timeout was cancelled before
+    /// it fired.
+    pub fn clear_timeout(&mut self, timeout: Timeout) -> bool {
+        // The deadline is removed from `deadlines` once it's expired.
+        self.timeouts.remove(&timeout.0).is_some()
+    }
+
+    /// Stops the event loop after the current iteration, see [`run`].
+    ///
+    /// [`run`]: EventLoop::run
+    pub fn shutdown(&mut self) {
+        self.running = false;
+    }
+
+    /// Returns `true` if the event loop is running, i.e. [`run`] was called
+    /// and [`shutdown`] wasn't called since.
+    ///
+    /// [`run`]: EventLoop::run
+    /// [`shutdown`]: EventLoop::shutdown
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Run the event loop until [`shutdown`] is called.
+    ///
+    /// To stop the event loop from another thread send a message using a
+    /// [`Sender`] and call [`shutdown`] in [`Handler::notify`].
+    ///
+    /// [`shutdown`]: EventLoop::shutdown
+    pub fn run(&mut self, handler: &mut H) -> io::Result<()> {
+        self.running = true;
+        while self.running {
+            match self.run_once(handler, None) {
+                Ok(()) => {}
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.running = false;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a single iteration of the event loop.
+    ///
+    /// This polls for events, blocking for at most `timeout` (or until the
+    /// next timeout expires), and handles all received events, messages and
+    /// expired timeouts.
+    pub fn run_once(&mut self, handler: &mut H, timeout: Option<Duration>) -> io::Result<()> {
+        let timeout = match (timeout, self.next_deadline()) {
+            (Some(timeout), Some(deadline)) => Some(cmp::min(timeout, deadline)),
+            (timeout, deadline) => timeout.or(deadline),
+        };
+
+        // Take the events so we can pass `self` to the handler.
+        let mut events = mem::replace(&mut self.events, Events::with_capacity(0));
+        let res = self.poll.poll(&mut events, timeout).and_then(|()| {
+            for event in events.iter() {
+                if event.token() == NOTIFY {
+                    self.notify(handler)?;
+                } else {
+                    handler.ready(self, event);
+                }
+            }
+            Ok(())
+        });
+        // Always put the events back, even if handling them failed.
+        self.events = events;
+        res?;
+
+        self.expire_timeouts(handler);
+        handler.tick(self);
+        Ok(())
+    }
+
+    /// Returns the duration until the next deadline, if any.
+    fn next_deadline(&mut self) -> Option<Duration> {
+        // Remove the deadlines of cancelled timeouts first.
+        while let Some(Reverse((_, id))) = self.deadlines.peek() {
+            if self.timeouts.contains_key(id) {
+                break;
+            }
+            let _ = self.deadlines.pop();
+        }
+
+        self.deadlines.peek().map(|Reverse((deadline, _))| {
+            deadline
+                .checked_duration_since(Instant::now())
+                .unwrap_or_else(|| Duration::from_millis(0))
+        })
+    }
+
+    fn notify(&mut self, handler: &mut H) -> io::Result<()> {
+        for _ in 0..MESSAGES_PER_TICK {
+            match self.receiver.try_recv() {
+                Ok(msg) => handler.notify(self, msg),
+                // We always hold a sender ourselves, so the channel can't be
+                // disconnected.
+                Err(_) => return Ok(()),
+            }
+        }
+        // There might be more messages, make sure we get back to them in the
+        // next iteration.
+        self.waker.wake()
+    }
+
+    fn expire_timeouts(&mut self, handler: &mut H) {
+        let now = Instant::now();
+        while let Some(Reverse((deadline, id))) = self.deadlines.peek() {
+            if *deadline > now {
+                break;
+            }
+            let id = *id;
+            let _ = self.deadlines.pop();
+            if let Some(timeout) = self.timeouts.remove(&id) {
+                handler.timeout(self, timeout);
+            }
+        }
+    }
+}
+
+impl<H: Handler> fmt::Debug for EventLoop<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventLoop")
+            .field("running", &self.running)
+            .field("poll", &self.poll)
+            .field("timeouts", &self.timeouts.len())
+            .finish()
+    }
+}
+
+/// Sends messages to an [`EventLoop`], created using
+/// [`EventLoop::channel`].
+pub struct Sender<M> {
+    sender: mpsc::Sender<M>,
+    waker: Arc<Waker>,
+}
+
+impl<M: Send> Sender<M> {
+    /// Send `msg` to the event loop, it will be delivered to
+    /// [`Handler::notify`].
+    pub fn send(&self, msg: M) -> Result<(), NotifyError<M>> {
+        self.sender
+            .send(msg)
+            .map_err(|mpsc::SendError(msg)| NotifyError::Closed(msg))?;
+        self.waker.wake().map_err(NotifyError::Io)
+    }
+}
+
+impl<M> Clone for Sender<M> {
+    fn clone(&self) -> Sender<M> {
+        Sender {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<M> fmt::Debug for Sender<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// Error returned by [`Sender::send`].
+pub enum NotifyError<M> {
+    /// The message was queued, but waking the event loop failed.
+    Io(io::Error),
+    /// The event loop is dropped, the message is returned.
+    Closed(M),
+}
+
+impl<M> fmt::Debug for NotifyError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Io(err) => f.debug_tuple("Io").field(err).finish(),
+            NotifyError::Closed(_) => f.debug_tuple("Closed").finish(),
+        }
+    }
+}
+
+impl<M> fmt::Display for NotifyError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Io(err) => write!(f, "failed to wake event loop: {}", err),
+            NotifyError::Closed(_) => f.write_str("event loop is closed"),
+        }
+    }
+}
+
+impl<M> error::Error for NotifyError<M> {}
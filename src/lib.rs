@@ -46,8 +46,10 @@ mod macros;
 
 mod interest;
 mod poll;
+mod registered;
 mod sys;
 mod token;
+mod token_map;
 mod waker;
 
 pub mod event;
@@ -63,10 +65,18 @@ cfg_net! {
 #[doc(no_inline)]
 pub use event::Events;
 pub use interest::Interest;
+#[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+pub use poll::Backend;
 pub use poll::{Poll, Registry};
+pub use registered::Registered;
 pub use token::Token;
+pub use token_map::TokenMap;
 pub use waker::Waker;
 
+#[cfg(feature = "event-loop")]
+#[cfg_attr(docsrs, doc(cfg(feature = "event-loop")))]
+pub mod event_loop;
+
 #[cfg(all(unix, feature = "os-ext"))]
 #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "os-ext"))))]
 pub mod unix {
@@ -115,6 +125,12 @@ pub mod features {
     #![cfg_attr(not(feature = "net"), doc = "## Network types (disabled)")]
     //!
     //! The `net` feature enables networking primitives in the `net` module.
+    //!
+    #![cfg_attr(feature = "event-loop", doc = "## `event-loop` (enabled)")]
+    #![cfg_attr(not(feature = "event-loop"), doc = "## `event-loop` (disabled)")]
+    //!
+    //! The `event-loop` feature enables the reference event loop in the
+    //! `event_loop` module, it implies `os-poll`.
 }
 
 pub mod guide {
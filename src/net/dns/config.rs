@@ -0,0 +1,201 @@
+use std::cmp;
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// Port DNS servers listen on.
+const DNS_PORT: u16 = 53;
+/// Defaults used by glibc, see `resolv.conf(5)`.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_ATTEMPTS: u32 = 2;
+/// Upper limits for `timeout` and `attempts` in `resolv.conf`.
+const MAX_TIMEOUT: u64 = 30;
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Configuration of a [`Resolver`].
+///
+/// [`Resolver`]: super::Resolver
+///
+/// # Examples
+///
+/// ```
+/// use mio::net::dns::ResolverConfig;
+/// use std::time::Duration;
+///
+/// let config = ResolverConfig::new()
+///     .with_nameserver("127.0.0.1:53".parse().unwrap())
+///     .with_host("example.internal", "10.0.0.1".parse().unwrap())
+///     .with_timeout(Duration::from_secs(1))
+///     .with_attempts(3);
+/// assert_eq!(config.nameservers(), &["127.0.0.1:53".parse().unwrap()]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ResolverConfig {
+    nameservers: Vec<SocketAddr>,
+    /// Lowercase host name -> addresses.
+    hosts: HashMap<String, Vec<IpAddr>>,
+    timeout: Duration,
+    attempts: u32,
+}
+
+impl ResolverConfig {
+    /// Create an empty configuration, without any name servers or hosts.
+    pub fn new() -> ResolverConfig {
+        ResolverConfig {
+            nameservers: Vec::new(),
+            hosts: HashMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+            attempts: DEFAULT_ATTEMPTS,
+        }
+    }
+
+    /// Load the system configuration from `/etc/resolv.conf` and
+    /// `/etc/hosts`.
+    ///
+    /// A missing file is treated as an empty file.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn system() -> io::Result<ResolverConfig> {
+        let config = ResolverConfig::new();
+        let config = config.with_resolv_conf(&read_optional("/etc/resolv.conf")?);
+        Ok(config.with_hosts(&read_optional("/etc/hosts")?))
+    }
+
+    /// Add the configuration in `contents`, in the format of
+    /// `resolv.conf(5)`.
+    ///
+    /// Only the `nameserver` entries and the `timeout` and `attempts`
+    /// options are used, other entries are ignored.
+    pub fn with_resolv_conf(mut self, contents: &str) -> ResolverConfig {
+        for line in lines(contents) {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("nameserver") => {
+                    if let Some(Ok(ip)) = fields.next().map(str::parse::<IpAddr>) {
+                        self.nameservers.push(SocketAddr::new(ip, DNS_PORT));
+                    }
+                }
+                Some("options") => {
+                    for option in fields {
+                        let mut parts = option.splitn(2, ':');
+                        let (name, value) = match (parts.next(), parts.next()) {
+                            (Some(name), Some(value)) => (name, value),
+                            _ => continue,
+                        };
+                        match (name, value.parse::<u32>()) {
+                            ("timeout", Ok(secs)) => {
+                                let secs = cmp::min(u64::from(secs), MAX_TIMEOUT);
+                                self.timeout = Duration::from_secs(secs);
+                            }
+                            ("attempts", Ok(attempts)) => {
+                                self = self.with_attempts(cmp::min(attempts, MAX_ATTEMPTS));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Add the hosts in `contents`, in the format of `hosts(5)`.
+    pub fn with_hosts(mut self, contents: &str) -> ResolverConfig {
+        for line in lines(contents) {
+            let mut fields = line.split_whitespace();
+            let ip = match fields.next().map(str::parse::<IpAddr>) {
+                Some(Ok(ip)) => ip,
+                _ => continue,
+            };
+            for name in fields {
+                self = self.with_host(name, ip);
+            }
+        }
+        self
+    }
+
+    /// Add a name server.
+    pub fn with_nameserver(mut self, addr: SocketAddr) -> ResolverConfig {
+        self.nameservers.push(addr);
+        self
+    }
+
+    /// Add a host, resolving `name` to `addr` without querying a name
+    /// server.
+    pub fn with_host(mut self, name: &str, addr: IpAddr) -> ResolverConfig {
+        let name = normalise(name);
+        let addrs = self.hosts.entry(name).or_default();
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+        self
+    }
+
+    /// Set the time to wait for a response from a name server before trying
+    /// the next name server. Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> ResolverConfig {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of times all name servers are tried. Defaults to 2.
+    pub fn with_attempts(mut self, attempts: u32) -> ResolverConfig {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Returns the name servers.
+    pub fn nameservers(&self) -> &[SocketAddr] {
+        &self.nameservers
+    }
+
+    /// Returns the addresses of host `name`, if any.
+    pub fn host(&self, name: &str) -> Option<&[IpAddr]> {
+        self.hosts.get(&normalise(name)).map(|addrs| &**addrs)
+    }
+
+    /// Returns the time to wait for a response from a name server.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the number of times all name servers are tried.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+impl Default for ResolverConfig {
+    fn default() -> ResolverConfig {
+        ResolverConfig::new()
+    }
+}
+
+/// Returns the non-empty lines of `contents` with comments removed.
+fn lines(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(|line| match line.find(&['#', ';'][..]) {
+            Some(idx) => &line[..idx],
+            None => line,
+        })
+        .filter(|line| !line.trim().is_empty())
+}
+
+fn normalise(name: &str) -> String {
+    name.strip_suffix('.').unwrap_or(name).to_ascii_lowercase()
+}
+
+#[cfg(unix)]
+fn read_optional(path: &str) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,200 @@
+//! Encoding of DNS queries and decoding of responses, see RFC 1035.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub(crate) const TYPE_A: u16 = 1;
+pub(crate) const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+const HEADER_LEN: usize = 12;
+/// Flags: response.
+const FLAG_QR: u16 = 1 << 15;
+/// Flags: truncated.
+const FLAG_TC: u16 = 1 << 9;
+/// Flags: recursion desired.
+const FLAG_RD: u16 = 1 << 8;
+
+const RCODE_NO_ERROR: u16 = 0;
+const RCODE_NAME_ERROR: u16 = 3;
+
+/// Maximum length of a name in its textual form, without the trailing dot.
+const MAX_NAME_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+/// Decoded response to a query.
+#[derive(Debug)]
+pub(crate) enum Response {
+    /// Addresses in the answer section, may be empty.
+    Answer(Vec<IpAddr>),
+    /// Response was truncated, the query must be retried over TCP.
+    Truncated,
+    /// Name doesn't exist.
+    NameError,
+    /// Any other response code, e.g. server failure or refused.
+    Failure(u16),
+}
+
+/// Encode a query for `name` with record type `qtype` and `id`.
+pub(crate) fn encode_query(id: u16, name: &str, qtype: u16) -> io::Result<Vec<u8>> {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(invalid_name());
+    }
+
+    let mut msg = Vec::with_capacity(HEADER_LEN + name.len() + 6);
+    msg.extend_from_slice(&id.to_be_bytes());
+    msg.extend_from_slice(&FLAG_RD.to_be_bytes());
+    // One question, no answer, authority or additional records.
+    msg.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(invalid_name());
+        }
+        msg.push(label.len() as u8);
+        msg.extend_from_slice(label.as_bytes());
+    }
+    msg.push(0);
+    msg.extend_from_slice(&qtype.to_be_bytes());
+    msg.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(msg)
+}
+
+/// Decode the response `msg` to the query with `id` for `name` with record
+/// type `qtype`.
+///
+/// Returns an error if `msg` isn't a valid response to the query. Following
+/// RFC 5452 section 9.1 the response must repeat the question of the query,
+/// matching the name case-insensitively, not just its id.
+pub(crate) fn decode_response(
+    msg: &[u8],
+    id: u16,
+    name: &str,
+    qtype: u16,
+) -> io::Result<Response> {
+    let mut reader = Reader { msg, pos: 0 };
+    if reader.u16()? != id {
+        return Err(invalid_response("mismatched id"));
+    }
+    let flags = reader.u16()?;
+    if flags & FLAG_QR == 0 {
+        return Err(invalid_response("not a response"));
+    }
+    let questions = reader.u16()?;
+    let answers = reader.u16()?;
+    // Skip the authority and additional record counts.
+    reader.skip(4)?;
+    if questions != 1
+        || !reader.name_eq(name)?
+        || reader.u16()? != qtype
+        || reader.u16()? != CLASS_IN
+    {
+        return Err(invalid_response("mismatched question"));
+    }
+    if flags & FLAG_TC != 0 {
+        return Ok(Response::Truncated);
+    }
+    match flags & 0xf {
+        RCODE_NO_ERROR => {}
+        RCODE_NAME_ERROR => return Ok(Response::NameError),
+        rcode => return Ok(Response::Failure(rcode)),
+    }
+
+    let mut addrs = Vec::new();
+    for _ in 0..answers {
+        reader.skip_name()?;
+        let rtype = reader.u16()?;
+        let class = reader.u16()?;
+        // Skip the TTL.
+        reader.skip(4)?;
+        let len = reader.u16()? as usize;
+        let data = reader.bytes(len)?;
+        if class != CLASS_IN || rtype != qtype {
+            // E.g. a CNAME record, the records for the canonical name follow.
+            continue;
+        }
+        match (rtype, len) {
+            (TYPE_A, 4) => {
+                let mut octets = [0; 4];
+                octets.copy_from_slice(data);
+                addrs.push(IpAddr::V4(Ipv4Addr::from(octets)));
+            }
+            (TYPE_AAAA, 16) => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(data);
+                addrs.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => return Err(invalid_response("invalid record length")),
+        }
+    }
+    Ok(Response::Answer(addrs))
+}
+
+struct Reader<'a> {
+    msg: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        match self.msg.get(self.pos..self.pos + n) {
+            Some(bytes) => {
+                self.pos += n;
+                Ok(bytes)
+            }
+            None => Err(invalid_response("message too short")),
+        }
+    }
+
+    fn skip(&mut self, n: usize) -> io::Result<()> {
+        self.bytes(n).map(|_| ())
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        self.bytes(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Read an uncompressed name, returning true if it's equal to `name`
+    /// ignoring ASCII case.
+    fn name_eq(&mut self, name: &str) -> io::Result<bool> {
+        let name = name.strip_suffix('.').unwrap_or(name);
+        let mut labels = name.split('.');
+        let mut equal = true;
+        loop {
+            let len = self.bytes(1)?[0];
+            if len & 0xc0 != 0 {
+                return Err(invalid_response("invalid label"));
+            }
+            let label = self.bytes(len as usize)?;
+            if len == 0 {
+                return Ok(equal && labels.next().is_none());
+            }
+            match labels.next() {
+                Some(expected) if expected.as_bytes().eq_ignore_ascii_case(label) => {}
+                _ => equal = false,
+            }
+        }
+    }
+
+    /// Skip a, possibly compressed, name.
+    fn skip_name(&mut self) -> io::Result<()> {
+        loop {
+            let len = self.bytes(1)?[0];
+            match len {
+                0 => return Ok(()),
+                // Pointer to a name elsewhere in the message, which ends the name.
+                len if len & 0xc0 == 0xc0 => return self.skip(1),
+                len if len & 0xc0 == 0 => self.skip(len as usize)?,
+                _ => return Err(invalid_response("invalid label")),
+            }
+        }
+    }
+}
+
+fn invalid_name() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "invalid host name")
+}
+
+fn invalid_response(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
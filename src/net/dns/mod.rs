@@ -0,0 +1,603 @@
+//! Non-blocking host name resolution.
+//!
+//! [`Resolver`] is a stub resolver: it resolves host names using
+//! [`ResolverConfig`] (e.g. `/etc/hosts`) or by sending queries to the
+//! (recursive) name servers configured in `/etc/resolv.conf`, without blocking.
+//! This allows resolving host names on the thread running the event loop,
+//! instead of blocking it using `getaddrinfo(3)` (as
+//! [`std::net::ToSocketAddrs`] does).
+//!
+//! Only the name servers and hosts are used from the system configuration,
+//! other sources (e.g. `nsswitch.conf(5)` on Linux) and search domains are not
+//! supported.
+
+mod config;
+mod message;
+
+pub use self::config::ResolverConfig;
+
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use self::message::{Response, TYPE_A, TYPE_AAAA};
+use crate::net::{TcpStream, UdpSocket};
+use crate::{event, Interest, Registry, Token};
+
+/// Maximum size of a response over UDP, we don't use EDNS.
+const MAX_UDP_SIZE: usize = 512;
+
+/// Identifier of a lookup started by [`Resolver::lookup`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Lookup(usize);
+
+/// A non-blocking DNS stub resolver.
+///
+/// Host names are looked up using [`lookup`], which looks up both the IPv6
+/// (AAAA) and IPv4 (A) addresses of the host. Addresses are looked up in the
+/// hosts of the [`ResolverConfig`] first, IP addresses are returned as is.
+/// Otherwise queries are sent over UDP to the name servers, falling back to
+/// TCP if a response is truncated. If a name server doesn't respond in time
+/// the next name server is tried, until all name servers are tried the
+/// configured number of attempts.
+///
+/// The resolver is an [`event::Source`]: it registers its sockets with the
+/// `Registry` it's registered with, all using the same `Token`. The interests
+/// passed when registering are ignored, the sockets are registered with the
+/// interests they require. After an event for its token, or after the
+/// [`timeout`] elapsed, the results of completed lookups are retrieved by
+/// calling [`poll`] until it returns `None`.
+///
+/// [`lookup`]: Resolver::lookup
+/// [`timeout`]: Resolver::timeout
+/// [`poll`]: Resolver::poll
+///
+/// # Examples
+///
+#[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+#[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::dns::{Resolver, ResolverConfig};
+/// use mio::{Events, Interest, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+///
+/// # let config = ResolverConfig::new().with_host("localhost", "127.0.0.1".parse()?);
+/// # #[cfg(any())]
+/// let config = ResolverConfig::system()?;
+/// let mut resolver = Resolver::new(config);
+/// poll.registry().register(&mut resolver, Token(0), Interest::READABLE)?;
+///
+/// let lookup = resolver.lookup("localhost")?;
+/// let addrs = 'outer: loop {
+///     while let Some((id, result)) = resolver.poll() {
+///         if id == lookup {
+///             break 'outer result?;
+///         }
+///     }
+///     poll.poll(&mut events, resolver.timeout())?;
+/// };
+/// println!("localhost: {:?}", addrs);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Resolver {
+    config: ResolverConfig,
+    registration: Option<(Registry, Token)>,
+    next_lookup: usize,
+    pending: Vec<PendingLookup>,
+    completed: VecDeque<(Lookup, io::Result<Vec<IpAddr>>)>,
+}
+
+impl Resolver {
+    /// Create a new resolver using `config`.
+    pub fn new(config: ResolverConfig) -> Resolver {
+        Resolver {
+            config,
+            registration: None,
+            next_lookup: 0,
+            pending: Vec::new(),
+            completed: VecDeque::new(),
+        }
+    }
+
+    /// Create a new resolver using the system configuration, see
+    /// [`ResolverConfig::system`].
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn system() -> io::Result<Resolver> {
+        ResolverConfig::system().map(Resolver::new)
+    }
+
+    /// Returns the configuration of the resolver.
+    pub fn config(&self) -> &ResolverConfig {
+        &self.config
+    }
+
+    /// Start looking up the addresses of `host`.
+    ///
+    /// The result is returned by [`Resolver::poll`], even if it's known
+    /// immediately (e.g. if `host` is an IP address). Returns an error if
+    /// `host` isn't a valid host name.
+    pub fn lookup(&mut self, host: &str) -> io::Result<Lookup> {
+        let result = if let Ok(ip) = host.parse::<IpAddr>() {
+            Some(vec![ip])
+        } else if let Some(addrs) = self.config.host(host) {
+            Some(addrs.to_vec())
+        } else {
+            // Validate the name before starting any queries.
+            let _ = message::encode_query(0, host, TYPE_A)?;
+            None
+        };
+
+        let lookup = Lookup(self.next_lookup);
+        self.next_lookup = self.next_lookup.wrapping_add(1);
+        if let Some(addrs) = result {
+            self.completed.push_back((lookup, Ok(addrs)));
+            return Ok(lookup);
+        }
+
+        let mut queries = Vec::with_capacity(2);
+        for &qtype in [TYPE_AAAA, TYPE_A].iter() {
+            let mut query = Query::new(host, qtype);
+            let err = io::Error::new(io::ErrorKind::NotFound, "no name servers configured");
+            query.retry(&self.config, &self.registration, err);
+            queries.push(query);
+        }
+        self.pending.push(PendingLookup { lookup, queries });
+        Ok(lookup)
+    }
+
+    /// Cancel the `lookup`, its result won't be returned by
+    /// [`Resolver::poll`].
+    ///
+    /// Returns false if the lookup is unknown or already completed.
+    pub fn cancel(&mut self, lookup: Lookup) -> bool {
+        if let Some(idx) = self.pending.iter().position(|p| p.lookup == lookup) {
+            let mut pending = self.pending.swap_remove(idx);
+            for query in pending.queries.iter_mut() {
+                query.close(&self.registration);
+            }
+            return true;
+        }
+        if let Some(idx) = self.completed.iter().position(|(id, _)| *id == lookup) {
+            let _ = self.completed.remove(idx);
+        }
+        false
+    }
+
+    /// Drive the lookups in progress, returning the result of a completed
+    /// lookup, if any.
+    ///
+    /// This must be called until it returns `None` after an event for the
+    /// resolver's token, after the [`Resolver::timeout`] elapsed and after
+    /// calling [`Resolver::lookup`].
+    ///
+    /// The addresses of a lookup are returned in the order returned by the
+    /// name servers, IPv6 addresses before IPv4 addresses. If the host
+    /// doesn't exist an error of kind `NotFound` is returned, if the name
+    /// servers didn't respond in time an error of kind `TimedOut`.
+    pub fn poll(&mut self) -> Option<(Lookup, io::Result<Vec<IpAddr>>)> {
+        let now = Instant::now();
+        let mut i = 0;
+        while i < self.pending.len() {
+            let pending = &mut self.pending[i];
+            for query in pending.queries.iter_mut() {
+                query.drive(now, &self.config, &self.registration);
+            }
+            if pending.queries.iter().all(|query| query.result.is_some()) {
+                let pending = self.pending.swap_remove(i);
+                self.completed
+                    .push_back((pending.lookup, pending.into_result()));
+            } else {
+                i += 1;
+            }
+        }
+        self.completed.pop_front()
+    }
+
+    /// Returns the time after which [`Resolver::poll`] must be called, even
+    /// if no event was received, e.g. to retry a query.
+    ///
+    /// This can be used as timeout for [`Poll::poll`].
+    ///
+    /// [`Poll::poll`]: crate::Poll::poll
+    pub fn timeout(&self) -> Option<Duration> {
+        if !self.completed.is_empty() {
+            return Some(Duration::from_millis(0));
+        }
+        let now = Instant::now();
+        self.pending
+            .iter()
+            .flat_map(|pending| pending.queries.iter())
+            .filter(|query| query.result.is_none())
+            .map(|query| query.deadline.saturating_duration_since(now))
+            .min()
+    }
+
+    fn transports(&mut self) -> impl Iterator<Item = &mut Transport> {
+        self.pending
+            .iter_mut()
+            .flat_map(|pending| pending.queries.iter_mut())
+            .filter_map(|query| query.transport.as_mut())
+    }
+}
+
+impl event::Source for Resolver {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        _interests: Interest,
+    ) -> io::Result<()> {
+        let registry = registry.shared();
+        for transport in self.transports() {
+            transport.register(&registry, token)?;
+        }
+        self.registration = Some((registry, token));
+        Ok(())
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        _interests: Interest,
+    ) -> io::Result<()> {
+        let registry = registry.shared();
+        for transport in self.transports() {
+            transport.reregister(&registry, token)?;
+        }
+        self.registration = Some((registry, token));
+        Ok(())
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        for transport in self.transports() {
+            transport.deregister(registry)?;
+        }
+        self.registration = None;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct PendingLookup {
+    lookup: Lookup,
+    /// AAAA and A queries.
+    queries: Vec<Query>,
+}
+
+impl PendingLookup {
+    fn into_result(self) -> io::Result<Vec<IpAddr>> {
+        let mut addrs = Vec::new();
+        let mut error = None;
+        for query in self.queries {
+            match query.result {
+                Some(Ok(found)) => addrs.extend(found),
+                Some(Err(err)) => {
+                    if error.is_none() {
+                        error = Some(err);
+                    }
+                }
+                None => unreachable!(),
+            }
+        }
+        if !addrs.is_empty() {
+            return Ok(addrs);
+        }
+        Err(error.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no addresses found")))
+    }
+}
+
+/// A single query, e.g. for the A records of a host.
+#[derive(Debug)]
+struct Query {
+    name: String,
+    qtype: u16,
+    id: u16,
+    /// Encoded query.
+    message: Vec<u8>,
+    /// Name server currently queried.
+    server: SocketAddr,
+    /// Number of name servers queried, including the current one.
+    tries: usize,
+    deadline: Instant,
+    /// `None` if no query is in flight.
+    transport: Option<Transport>,
+    result: Option<io::Result<Vec<IpAddr>>>,
+}
+
+impl Query {
+    fn new(name: &str, qtype: u16) -> Query {
+        Query {
+            name: name.to_owned(),
+            qtype,
+            id: 0,
+            message: Vec::new(),
+            server: SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+            tries: 0,
+            deadline: Instant::now(),
+            transport: None,
+            result: None,
+        }
+    }
+
+    /// Send the query to the next name server over UDP.
+    fn start(
+        &mut self,
+        config: &ResolverConfig,
+        registration: &Option<(Registry, Token)>,
+    ) -> io::Result<()> {
+        let nameservers = config.nameservers();
+        self.server = nameservers[self.tries % nameservers.len()];
+        self.tries += 1;
+        self.id = random_id();
+        self.message = message::encode_query(self.id, &self.name, self.qtype)?;
+        self.deadline = Instant::now() + config.timeout();
+
+        let unspecified: IpAddr = match self.server {
+            SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+            SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+        socket.connect(self.server)?;
+        match socket.send(&self.message) {
+            // If the send buffer is full the query is retried after the
+            // timeout.
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => return Err(err),
+            Ok(_) => {}
+        }
+        let mut transport = Transport::Udp(socket);
+        if let Some((registry, token)) = registration {
+            transport.register(registry, *token)?;
+        }
+        self.transport = Some(transport);
+        Ok(())
+    }
+
+    /// Retry the truncated query over TCP, using the same name server.
+    fn start_tcp(&mut self, registration: &Option<(Registry, Token)>) -> io::Result<()> {
+        let stream = TcpStream::connect(self.server)?;
+        let mut send = Vec::with_capacity(2 + self.message.len());
+        send.extend_from_slice(&(self.message.len() as u16).to_be_bytes());
+        send.extend_from_slice(&self.message);
+        let mut transport = Transport::Tcp {
+            stream,
+            send,
+            sent: 0,
+            recv: Vec::new(),
+        };
+        if let Some((registry, token)) = registration {
+            transport.register(registry, *token)?;
+        }
+        self.transport = Some(transport);
+        Ok(())
+    }
+
+    /// Try the next name server, or fail with `err` if all name servers were
+    /// tried the configured number of attempts.
+    fn retry(
+        &mut self,
+        config: &ResolverConfig,
+        registration: &Option<(Registry, Token)>,
+        mut err: io::Error,
+    ) {
+        self.close(registration);
+        let max_tries = config.nameservers().len() * config.attempts() as usize;
+        while self.tries < max_tries {
+            match self.start(config, registration) {
+                Ok(()) => return,
+                Err(e) => {
+                    self.close(registration);
+                    err = e;
+                }
+            }
+        }
+        self.result = Some(Err(err));
+    }
+
+    fn finish(&mut self, registration: &Option<(Registry, Token)>, result: io::Result<Vec<IpAddr>>) {
+        self.close(registration);
+        self.result = Some(result);
+    }
+
+    fn close(&mut self, registration: &Option<(Registry, Token)>) {
+        if let (Some(mut transport), Some((registry, _))) = (self.transport.take(), registration) {
+            // The socket is closed anyway.
+            let _ = transport.deregister(registry);
+        }
+    }
+
+    /// Process the responses received, if any, and handle the timeout.
+    fn drive(
+        &mut self,
+        now: Instant,
+        config: &ResolverConfig,
+        registration: &Option<(Registry, Token)>,
+    ) {
+        if self.result.is_some() {
+            return;
+        }
+
+        loop {
+            let (response, is_udp) = match self.transport.as_mut() {
+                Some(Transport::Udp(socket)) => {
+                    let mut buf = [0; MAX_UDP_SIZE];
+                    match socket.recv(&mut buf) {
+                        Ok(n) => match message::decode_response(&buf[..n], self.id, &self.name, self.qtype) {
+                            Ok(response) => (response, true),
+                            // Ignore invalid responses, e.g. responses to a
+                            // previous query.
+                            Err(_) => continue,
+                        },
+                        Err(ref err) if is_would_block(err) => break,
+                        Err(err) => return self.retry(config, registration, err),
+                    }
+                }
+                Some(Transport::Tcp {
+                    stream,
+                    send,
+                    sent,
+                    recv,
+                }) => {
+                    let result = drive_tcp(stream, send, sent, recv).and_then(|msg| match msg {
+                        Some(msg) => message::decode_response(&msg, self.id, &self.name, self.qtype).map(Some),
+                        None => Ok(None),
+                    });
+                    match result {
+                        Ok(Some(response)) => (response, false),
+                        Ok(None) => break,
+                        Err(err) => return self.retry(config, registration, err),
+                    }
+                }
+                None => break,
+            };
+
+            match response {
+                Response::Answer(addrs) => return self.finish(registration, Ok(addrs)),
+                Response::Truncated if is_udp => {
+                    self.close(registration);
+                    if let Err(err) = self.start_tcp(registration) {
+                        return self.retry(config, registration, err);
+                    }
+                    self.deadline = now + config.timeout();
+                }
+                Response::Truncated => {
+                    let err = io::Error::new(io::ErrorKind::InvalidData, "truncated response");
+                    return self.retry(config, registration, err);
+                }
+                Response::NameError => {
+                    let err = io::Error::new(io::ErrorKind::NotFound, "host not found");
+                    return self.finish(registration, Err(err));
+                }
+                Response::Failure(rcode) => {
+                    let msg = format!("name server returned error code {}", rcode);
+                    let err = io::Error::new(rcode_error_kind(rcode), msg);
+                    return self.retry(config, registration, err);
+                }
+            }
+        }
+
+        if now >= self.deadline {
+            let err = io::Error::new(io::ErrorKind::TimedOut, "name server timed out");
+            self.retry(config, registration, err);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Transport {
+    Udp(UdpSocket),
+    Tcp {
+        stream: TcpStream,
+        /// Length prefixed query.
+        send: Vec<u8>,
+        /// Bytes of `send` written.
+        sent: usize,
+        /// Length prefixed response, possibly incomplete.
+        recv: Vec<u8>,
+    },
+}
+
+impl Transport {
+    fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        match self {
+            Transport::Udp(socket) => registry.register(socket, token, Interest::READABLE),
+            Transport::Tcp { stream, .. } => {
+                registry.register(stream, token, Interest::READABLE | Interest::WRITABLE)
+            }
+        }
+    }
+
+    fn reregister(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        match self {
+            Transport::Udp(socket) => registry.reregister(socket, token, Interest::READABLE),
+            Transport::Tcp { stream, .. } => {
+                registry.reregister(stream, token, Interest::READABLE | Interest::WRITABLE)
+            }
+        }
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        match self {
+            Transport::Udp(socket) => registry.deregister(socket),
+            Transport::Tcp { stream, .. } => registry.deregister(stream),
+        }
+    }
+}
+
+/// Write the query and read the response, returns the response once it's
+/// completely read.
+fn drive_tcp(
+    stream: &mut TcpStream,
+    send: &[u8],
+    sent: &mut usize,
+    recv: &mut Vec<u8>,
+) -> io::Result<Option<Vec<u8>>> {
+    while *sent < send.len() {
+        match stream.write(&send[*sent..]) {
+            Ok(n) => *sent += n,
+            Err(ref err) if is_would_block(err) => return Ok(None),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut buf = [0; 1024];
+    loop {
+        if recv.len() >= 2 {
+            let len = u16::from_be_bytes([recv[0], recv[1]]) as usize;
+            if recv.len() >= 2 + len {
+                return Ok(Some(recv[2..2 + len].to_vec()));
+            }
+        }
+        match stream.read(&mut buf) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "name server closed the connection",
+                ))
+            }
+            Ok(n) => recv.extend_from_slice(&buf[..n]),
+            Err(ref err) if is_would_block(err) => return Ok(None),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Returns true if the operation should be retried once the socket is ready,
+/// including while the TCP stream is still connecting.
+fn is_would_block(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::NotConnected
+    )
+}
+
+/// Returns the error kind for a response code other than "no error" or "name
+/// error".
+fn rcode_error_kind(rcode: u16) -> io::ErrorKind {
+    match rcode {
+        // Refused.
+        5 => io::ErrorKind::ConnectionRefused,
+        // E.g. server failure.
+        _ => io::ErrorKind::Other,
+    }
+}
+
+/// Returns a random query id, making it harder to spoof responses.
+fn random_id() -> u16 {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u32(now.subsec_nanos());
+    hasher.finish() as u16
+}
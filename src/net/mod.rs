@@ -8,10 +8,16 @@
 //! [portability guidelines]: ../struct.Poll.html#portability
 
 mod tcp;
-pub use self::tcp::{TcpKeepalive, TcpListener, TcpSocket, TcpStream};
+pub use self::tcp::{HappyEyeballs, TcpKeepalive, TcpListener, TcpSocket, TcpStream};
+pub use self::tcp::TcpInfo;
+
+pub mod dns;
 
 mod udp;
-pub use self::udp::UdpSocket;
+pub use self::udp::{RecvMeta, UdpSocket};
+
+mod udp_builder;
+pub use self::udp_builder::UdpSocketBuilder;
 
 #[cfg(unix)]
 mod uds;
@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::net::{TcpSocket, TcpStream};
+use crate::{Interest, Registry, Token};
+
+/// Default delay between starting connection attempts, as recommended by
+/// RFC 8305.
+const DEFAULT_DELAY: Duration = Duration::from_millis(250);
+
+/// A non-blocking "Happy Eyeballs" (RFC 8305) connector.
+///
+/// Connects to one of multiple addresses of the same host, e.g. the IPv6 and
+/// IPv4 addresses of a dual-stack host, without waiting for an attempt to
+/// time out before trying the next address. The addresses are reordered to
+/// alternate between IPv6 and IPv4 (starting with the family of the first
+/// address), after which a connection attempt is started for the first
+/// address. If that attempt hasn't completed within the connection attempt
+/// delay (250 milliseconds by default, see [`with_delay`]) an attempt is
+/// started for the next address, racing the first one, and so on. A failed
+/// attempt starts the next attempt immediately. The first attempt to connect
+/// wins, all other attempts are closed.
+///
+/// The connector is driven by calling [`poll`], which registers all
+/// connection attempts with the [`Registry`] using the same [`Token`]. It
+/// must be called again after an event for that token and after the
+/// [`timeout`] elapsed.
+///
+/// [`with_delay`]: HappyEyeballs::with_delay
+/// [`poll`]: HappyEyeballs::poll
+/// [`timeout`]: HappyEyeballs::timeout
+///
+/// # Examples
+///
+#[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+#[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+/// # use std::error::Error;
+/// # use std::net;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::HappyEyeballs;
+/// use mio::{Events, Poll, Token};
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+///
+/// let listener = net::TcpListener::bind("127.0.0.1:0")?;
+/// let port = listener.local_addr()?.port();
+/// let addrs = vec![
+///     net::SocketAddr::new("::1".parse()?, port),
+///     net::SocketAddr::new("127.0.0.1".parse()?, port),
+/// ];
+///
+/// let mut connector = HappyEyeballs::new(addrs);
+/// let stream = loop {
+///     // Returns an error once all attempts failed.
+///     if let Some(stream) = connector.poll(poll.registry(), Token(0))? {
+///         break stream;
+///     }
+///     poll.poll(&mut events, connector.timeout())?;
+/// };
+/// assert_eq!(stream.peer_addr()?.port(), port);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HappyEyeballs {
+    /// Addresses not yet attempted, in the order to attempt them.
+    addrs: VecDeque<SocketAddr>,
+    /// Connection attempts in progress.
+    attempts: Vec<TcpStream>,
+    delay: Duration,
+    /// When to start the next attempt, `None` means as soon as possible.
+    next_attempt: Option<Instant>,
+    /// Error of the last failed attempt.
+    last_error: Option<io::Error>,
+}
+
+impl HappyEyeballs {
+    /// Create a new connector for `addrs`.
+    ///
+    /// No connection attempts are started until [`HappyEyeballs::poll`] is
+    /// called.
+    pub fn new<I>(addrs: I) -> HappyEyeballs
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        HappyEyeballs {
+            addrs: interleave(addrs),
+            attempts: Vec::new(),
+            delay: DEFAULT_DELAY,
+            next_attempt: None,
+            last_error: None,
+        }
+    }
+
+    /// Set the connection attempt delay, the time to wait for an attempt
+    /// before starting the next one. Defaults to 250 milliseconds.
+    pub fn with_delay(mut self, delay: Duration) -> HappyEyeballs {
+        self.delay = delay;
+        self
+    }
+
+    /// Drive the connection attempts.
+    ///
+    /// Checks the connection attempts in progress and starts new attempts
+    /// when needed. Each attempt is registered with `registry` using `token`
+    /// and [`Interest::WRITABLE`].
+    ///
+    /// Returns the connected stream of the winning attempt, at which point
+    /// all other attempts are closed. The returned stream is deregistered,
+    /// it needs to be registered again to receive events. Returns `Ok(None)`
+    /// if no attempt connected yet, in which case this must be called again
+    /// after an event for `token` or after [`HappyEyeballs::timeout`].
+    ///
+    /// Returns the error of the last attempt once all attempts failed.
+    pub fn poll(&mut self, registry: &Registry, token: Token) -> io::Result<Option<TcpStream>> {
+        let mut i = 0;
+        while i < self.attempts.len() {
+            match is_connected(&self.attempts[i]) {
+                Ok(true) => {
+                    let mut stream = self.attempts.swap_remove(i);
+                    self.cancel(registry);
+                    registry.deregister(&mut stream)?;
+                    return Ok(Some(stream));
+                }
+                Ok(false) => i += 1,
+                Err(err) => {
+                    let mut stream = self.attempts.swap_remove(i);
+                    // The stream is dropped (closing it) anyway.
+                    let _ = registry.deregister(&mut stream);
+                    self.last_error = Some(err);
+                    self.next_attempt = None;
+                }
+            }
+        }
+
+        loop {
+            let now = Instant::now();
+            let due = match self.next_attempt {
+                Some(at) => at <= now,
+                None => true,
+            };
+            if !due && !self.attempts.is_empty() {
+                break;
+            }
+            let addr = match self.addrs.pop_front() {
+                Some(addr) => addr,
+                None => break,
+            };
+            match connect(registry, token, addr) {
+                Ok(stream) => {
+                    self.attempts.push(stream);
+                    self.next_attempt = Some(now + self.delay);
+                }
+                Err(err) => {
+                    self.last_error = Some(err);
+                    self.next_attempt = None;
+                }
+            }
+        }
+
+        if self.attempts.is_empty() {
+            Err(self.last_error.take().unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to")
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the time after which [`HappyEyeballs::poll`] must be called
+    /// again to start the next connection attempt, if any.
+    ///
+    /// This can be used as timeout for [`Poll::poll`].
+    ///
+    /// [`Poll::poll`]: crate::Poll::poll
+    pub fn timeout(&self) -> Option<Duration> {
+        if self.addrs.is_empty() {
+            return None;
+        }
+        match self.next_attempt {
+            Some(at) => Some(at.saturating_duration_since(Instant::now())),
+            None => Some(Duration::from_millis(0)),
+        }
+    }
+
+    /// Close all connection attempts in progress and forget about the
+    /// addresses not yet attempted.
+    fn cancel(&mut self, registry: &Registry) {
+        for mut stream in self.attempts.drain(..) {
+            let _ = registry.deregister(&mut stream);
+        }
+        self.addrs.clear();
+        self.next_attempt = None;
+    }
+}
+
+/// Reorder `addrs` to alternate between address families, starting with the
+/// family of the first address, see RFC 8305 section 4.
+fn interleave<I>(addrs: I) -> VecDeque<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let addrs: Vec<SocketAddr> = addrs.into_iter().collect();
+    let first_is_ipv6 = match addrs.first() {
+        Some(addr) => addr.is_ipv6(),
+        None => return VecDeque::new(),
+    };
+    let (mut first, mut second): (VecDeque<SocketAddr>, VecDeque<SocketAddr>) = addrs
+        .iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut interleaved = VecDeque::with_capacity(addrs.len());
+    loop {
+        match (first.pop_front(), second.pop_front()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+fn connect(registry: &Registry, token: Token, addr: SocketAddr) -> io::Result<TcpStream> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    let mut stream = socket.connect(addr)?;
+    registry.register(&mut stream, token, Interest::WRITABLE)?;
+    Ok(stream)
+}
+
+/// Returns `Ok(true)` if `stream` is connected, `Ok(false)` if the connection
+/// attempt is still in progress.
+fn is_connected(stream: &TcpStream) -> io::Result<bool> {
+    if let Some(err) = stream.take_error()? {
+        return Err(err);
+    }
+    match stream.peer_addr() {
+        Ok(_) => Ok(true),
+        Err(ref err) if err.kind() == io::ErrorKind::NotConnected => Ok(false),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+/// Statistics of a TCP connection or listener, retrieved using
+/// `getsockopt(TCP_INFO)`, or `TCP_CONNECTION_INFO` on macOS and iOS.
+///
+/// Returned by [`TcpStream::tcp_info`] and [`TcpListener::tcp_info`]. Values
+/// not reported by the platform, or the running kernel version, are `None`.
+/// The connection statistics are `None` for listeners and the accept queue
+/// statistics are `None` for streams.
+///
+/// | Statistic          | Linux, Android | FreeBSD | macOS, iOS |
+/// |--------------------|----------------|---------|------------|
+/// | `rtt`, `rtt_var`   | yes            | yes     | yes        |
+/// | `cwnd`             | yes            | yes     | yes        |
+/// | `retransmits`      | yes            | yes     | yes        |
+/// | `delivery_rate`    | Linux 4.9+     | no      | no         |
+/// | `unacked`          | yes            | no      | no         |
+/// | `accept_queue_len` | yes            | yes     | no         |
+/// | `accept_queue_max` | yes            | yes     | no         |
+///
+/// [`TcpStream::tcp_info`]: crate::net::TcpStream::tcp_info
+/// [`TcpListener::tcp_info`]: crate::net::TcpListener::tcp_info
+#[derive(Clone, Debug, Default)]
+pub struct TcpInfo {
+    pub(crate) rtt: Option<Duration>,
+    pub(crate) rtt_var: Option<Duration>,
+    pub(crate) cwnd: Option<u32>,
+    pub(crate) retransmits: Option<u32>,
+    pub(crate) delivery_rate: Option<u64>,
+    pub(crate) unacked: Option<u32>,
+    pub(crate) accept_queue_len: Option<u32>,
+    pub(crate) accept_queue_max: Option<u32>,
+}
+
+impl TcpInfo {
+    /// Returns the smoothed round trip time.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Returns the round trip time variance.
+    pub fn rtt_var(&self) -> Option<Duration> {
+        self.rtt_var
+    }
+
+    /// Returns the congestion window, in segments.
+    pub fn cwnd(&self) -> Option<u32> {
+        self.cwnd
+    }
+
+    /// Returns the total number of retransmitted segments.
+    pub fn retransmits(&self) -> Option<u32> {
+        self.retransmits
+    }
+
+    /// Returns the most recent delivery rate, in bytes per second.
+    pub fn delivery_rate(&self) -> Option<u64> {
+        self.delivery_rate
+    }
+
+    /// Returns the number of segments that were sent but not yet
+    /// acknowledged.
+    pub fn unacked(&self) -> Option<u32> {
+        self.unacked
+    }
+
+    /// Returns the number of connections in the accept queue of a listener,
+    /// i.e. established connections that haven't been accepted yet.
+    pub fn accept_queue_len(&self) -> Option<u32> {
+        self.accept_queue_len
+    }
+
+    /// Returns the maximum length of the accept queue of a listener, i.e. the
+    /// backlog passed to `listen(2)`.
+    pub fn accept_queue_max(&self) -> Option<u32> {
+        self.accept_queue_max
+    }
+}
@@ -3,9 +3,18 @@ use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
+use std::time::Duration;
 use std::{fmt, io};
 
-use super::{TcpSocket, TcpStream};
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use super::TcpInfo;
+use super::{TcpKeepalive, TcpSocket, TcpStream};
 use crate::io_source::IoSource;
 use crate::{event, sys, Interest, Registry, Token};
 
@@ -51,7 +60,26 @@ impl TcpListener {
     /// 4. Calls `listen` on the socket to prepare it to receive new connections.
     pub fn bind(addr: SocketAddr) -> io::Result<TcpListener> {
         let socket = TcpSocket::new_for_addr(addr)?;
+        TcpListener::bind_socket(socket, addr)
+    }
+
+    /// Same as [`TcpListener::bind`], but using a Multipath TCP (MPTCP)
+    /// socket, see [`TcpSocket::new_mptcp_v4`].
+    ///
+    /// Connections from peers that don't support MPTCP fall back to plain TCP,
+    /// which can be checked using [`TcpStream::is_mptcp`] on the accepted
+    /// streams.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_mptcp(addr: SocketAddr) -> io::Result<TcpListener> {
+        let socket = TcpSocket::new_mptcp_for_addr(addr)?;
+        TcpListener::bind_socket(socket, addr)
+    }
 
+    fn bind_socket(socket: TcpSocket, addr: SocketAddr) -> io::Result<TcpListener> {
         // On platforms with Berkeley-derived sockets, this allows to quickly
         // rebind a socket, without needing to wait for the OS to clean up the
         // previous one.
@@ -122,6 +150,211 @@ impl TcpListener {
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.inner.take_error()
     }
+
+    /// Sets the value of `SO_LINGER` on this socket.
+    ///
+    /// See [`TcpSocket::set_linger`].
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        sys::tcp::set_linger(self.sys(), dur)
+    }
+
+    /// Gets the value of `SO_LINGER` on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_linger(self.sys())
+    }
+
+    /// Sets the value of `SO_RCVBUF` on this socket.
+    pub fn set_recv_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_recv_buffer_size(self.sys(), size)
+    }
+
+    /// Gets the value of `SO_RCVBUF` set on this socket.
+    ///
+    /// The returned value may differ from the value passed to
+    /// [`set_recv_buffer_size`], see [`TcpSocket::get_recv_buffer_size`].
+    ///
+    /// [`set_recv_buffer_size`]: TcpListener::set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_recv_buffer_size(self.sys())
+    }
+
+    /// Sets the value of `SO_SNDBUF` on this socket.
+    pub fn set_send_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_send_buffer_size(self.sys(), size)
+    }
+
+    /// Gets the value of `SO_SNDBUF` set on this socket.
+    ///
+    /// The returned value may differ from the value passed to
+    /// [`set_send_buffer_size`], see [`TcpSocket::get_send_buffer_size`].
+    ///
+    /// [`set_send_buffer_size`]: TcpListener::set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_send_buffer_size(self.sys())
+    }
+
+    /// Sets whether keepalive messages are enabled to be sent on this socket.
+    ///
+    /// This will set the `SO_KEEPALIVE` option on this socket.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        sys::tcp::set_keepalive(self.sys(), keepalive)
+    }
+
+    /// Returns whether or not TCP keepalive probes will be sent by this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        sys::tcp::get_keepalive(self.sys())
+    }
+
+    /// Sets parameters configuring TCP keepalive probes for this socket,
+    /// enabling TCP keepalive.
+    ///
+    /// See [`TcpSocket::set_keepalive_params`].
+    pub fn set_keepalive_params(&self, keepalive: TcpKeepalive) -> io::Result<()> {
+        self.set_keepalive(true)?;
+        sys::tcp::set_keepalive_params(self.sys(), keepalive)
+    }
+
+    /// Returns the amount of time after which TCP keepalive probes will be sent
+    /// on idle connections, or `None` if keepalive messages are disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_time`].
+    #[cfg_attr(docsrs, doc(cfg(not(target_os = "windows"))))]
+    #[cfg(not(target_os = "windows"))]
+    pub fn keepalive_time(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_keepalive_time(self.sys())
+    }
+
+    /// Returns the time interval between TCP keepalive probes, or `None` if
+    /// keepalive messages are disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_interval`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        )))
+    )]
+    #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+    pub fn keepalive_interval(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_keepalive_interval(self.sys())
+    }
+
+    /// Returns the maximum number of TCP keepalive probes that will be sent
+    /// before dropping a connection, or `None` if keepalive messages are
+    /// disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_retries`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        )))
+    )]
+    #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+    pub fn keepalive_retries(&self) -> io::Result<Option<u32>> {
+        sys::tcp::get_keepalive_retries(self.sys())
+    }
+
+    /// Sets the value of `SO_BINDTODEVICE` on this socket.
+    ///
+    /// See [`TcpSocket::bind_device`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_device(&self, interface: &[u8]) -> io::Result<()> {
+        sys::tcp::set_bind_device(self.sys(), interface)
+    }
+
+    /// Gets the value of `SO_BINDTODEVICE` on this socket, returning `None`
+    /// if the socket isn't bound to an interface.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
+        sys::tcp::get_bind_device(self.sys())
+    }
+
+    /// Sets the value of `SO_MARK` on this socket.
+    ///
+    /// See [`TcpSocket::set_mark`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        sys::tcp::set_mark(self.sys(), mark)
+    }
+
+    /// Gets the value of `SO_MARK` on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mark(&self) -> io::Result<u32> {
+        sys::tcp::get_mark(self.sys())
+    }
+
+    /// Returns statistics of the listener, such as the number of connections
+    /// waiting to be accepted, using `getsockopt(TCP_INFO)`.
+    ///
+    /// This uses `TCP_CONNECTION_INFO` on macOS and iOS, see [`TcpInfo`] for
+    /// the statistics reported per platform.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        )))
+    )]
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        sys::tcp::get_tcp_info(self.sys())
+    }
+
+    #[cfg(unix)]
+    fn sys(&self) -> sys::tcp::TcpSocket {
+        self.inner.as_raw_fd()
+    }
+
+    #[cfg(windows)]
+    fn sys(&self) -> sys::tcp::TcpSocket {
+        self.inner.as_raw_socket() as _
+    }
 }
 
 impl event::Source for TcpListener {
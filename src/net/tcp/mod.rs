@@ -1,3 +1,9 @@
+mod happy_eyeballs;
+pub use self::happy_eyeballs::HappyEyeballs;
+
+mod info;
+pub use self::info::TcpInfo;
+
 mod listener;
 pub use self::listener::TcpListener;
 
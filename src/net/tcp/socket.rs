@@ -60,6 +60,47 @@ impl TcpSocket {
         sys::tcp::new_v6_socket().map(|sys| TcpSocket { sys })
     }
 
+    /// Create a new IPv4 Multipath TCP (MPTCP) socket.
+    ///
+    /// This calls `socket(2)` with `IPPROTO_MPTCP`. The socket is used like any
+    /// other TCP socket and converts into ordinary [`TcpStream`]s and
+    /// [`TcpListener`]s. If the peer doesn't support MPTCP the connection
+    /// falls back to plain TCP, which can be checked using
+    /// [`TcpStream::is_mptcp`].
+    ///
+    /// Requires Linux 5.6 or later with MPTCP enabled (the `net.mptcp.enabled`
+    /// sysctl), otherwise an error is returned (`EPROTONOSUPPORT` or
+    /// `ENOPROTOOPT`).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn new_mptcp_v4() -> io::Result<TcpSocket> {
+        sys::tcp::new_mptcp_v4_socket().map(|sys| TcpSocket { sys })
+    }
+
+    /// Create a new IPv6 Multipath TCP (MPTCP) socket.
+    ///
+    /// See [`TcpSocket::new_mptcp_v4`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn new_mptcp_v6() -> io::Result<TcpSocket> {
+        sys::tcp::new_mptcp_v6_socket().map(|sys| TcpSocket { sys })
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub(crate) fn new_mptcp_for_addr(addr: SocketAddr) -> io::Result<TcpSocket> {
+        if addr.is_ipv4() {
+            TcpSocket::new_mptcp_v4()
+        } else {
+            TcpSocket::new_mptcp_v6()
+        }
+    }
+
     pub(crate) fn new_for_addr(addr: SocketAddr) -> io::Result<TcpSocket> {
         if addr.is_ipv4() {
             TcpSocket::new_v4()
@@ -86,6 +127,34 @@ impl TcpSocket {
         Ok(TcpStream::from_std(stream))
     }
 
+    /// Connect the socket to `addr` using TCP Fast Open.
+    ///
+    /// Unlike [`connect`] this doesn't start the connection, the SYN is sent
+    /// by the first write to the returned stream, carrying the written data.
+    /// This saves a round trip when reconnecting to a server from which a Fast
+    /// Open cookie was previously received. Without a cookie, or if the server
+    /// doesn't support Fast Open, the data is sent after the connection is
+    /// established, as with a regular connect. The stream is writable before
+    /// the connection is established.
+    ///
+    /// This sets `TCP_FASTOPEN_CONNECT` on the socket. If the kernel doesn't
+    /// support it (Linux 4.11+) this is a regular [`connect`]. Whether Fast
+    /// Open is used also depends on the `net.ipv4.tcp_fastopen` sysctl.
+    ///
+    /// [`connect`]: TcpSocket::connect
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn connect_fastopen(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let stream = sys::tcp::connect_fastopen(self.sys, addr)?;
+
+        // Don't close the socket
+        mem::forget(self);
+        Ok(TcpStream::from_std(stream))
+    }
+
     /// Listen for inbound connections, converting the socket to a
     /// `TcpListener`.
     pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
@@ -120,6 +189,25 @@ impl TcpSocket {
         sys::tcp::get_reuseport(self.sys)
     }
 
+    /// Sets the value of `IPV6_V6ONLY` on this socket.
+    ///
+    /// If set to `true` an IPv6 socket only communicates using IPv6, e.g. a
+    /// listener bound to `[::]` doesn't accept IPv4 connections. If set to
+    /// `false` it also communicates with IPv4 peers using IPv4-mapped IPv6
+    /// addresses. The default depends on the platform (on Linux, the
+    /// `net.ipv6.bindv6only` sysctl), setting it explicitly makes the
+    /// behaviour the same everywhere.
+    ///
+    /// This must be called before `bind` and is only valid for IPv6 sockets.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        sys::tcp::set_only_v6(self.sys, only_v6)
+    }
+
+    /// Get the value of `IPV6_V6ONLY` set on this socket.
+    pub fn get_only_v6(&self) -> io::Result<bool> {
+        sys::tcp::get_only_v6(self.sys)
+    }
+
     /// Sets the value of `SO_LINGER` on this socket.
     pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
         sys::tcp::set_linger(self.sys, dur)
@@ -321,6 +409,309 @@ impl TcpSocket {
         sys::tcp::get_keepalive_retries(self.sys)
     }
 
+    /// Sets the value of `SO_BUSY_POLL` on this socket.
+    ///
+    /// This sets the approximate time to busy poll the network device queue on
+    /// blocking receives, or when the socket is polled using [`Poll`] with
+    /// busy polling enabled (see [`Poll::set_busy_poll`]). Increasing the value
+    /// beyond the `net.core.busy_read` sysctl requires the `CAP_NET_ADMIN`
+    /// capability.
+    ///
+    /// Linux uses microsecond precision, sub-microsecond specifications are
+    /// omitted.
+    ///
+    /// [`Poll`]: crate::Poll
+    /// [`Poll::set_busy_poll`]: crate::Poll::set_busy_poll
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_busy_poll(&self, dur: Duration) -> io::Result<()> {
+        sys::tcp::set_busy_poll(self.sys, dur)
+    }
+
+    /// Get the value of `SO_BUSY_POLL` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_busy_poll(&self) -> io::Result<Duration> {
+        sys::tcp::get_busy_poll(self.sys)
+    }
+
+    /// Sets the value of `SO_PREFER_BUSY_POLL` on this socket.
+    ///
+    /// When set the kernel prefers busy polling over softirq processing for
+    /// the network device queue of this socket. Requires Linux 5.11 or later.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_prefer_busy_poll(&self, prefer: bool) -> io::Result<()> {
+        sys::tcp::set_prefer_busy_poll(self.sys, prefer)
+    }
+
+    /// Get the value of `SO_PREFER_BUSY_POLL` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_prefer_busy_poll(&self) -> io::Result<bool> {
+        sys::tcp::get_prefer_busy_poll(self.sys)
+    }
+
+    /// Sets the value of `TCP_FASTOPEN` on this socket, enabling TCP Fast
+    /// Open on the listener created by [`listen`].
+    ///
+    /// `queue_len` is the maximum length of the queue of pending Fast Open
+    /// requests (connections that haven't completed the three-way handshake
+    /// yet), a value of zero disables Fast Open. This must be called before
+    /// `listen`. Whether Fast Open is used also depends on the
+    /// `net.ipv4.tcp_fastopen` sysctl.
+    ///
+    /// [`listen`]: TcpSocket::listen
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        sys::tcp::set_fastopen(self.sys, queue_len)
+    }
+
+    /// Get the value of `TCP_FASTOPEN` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_fastopen(&self) -> io::Result<u32> {
+        sys::tcp::get_fastopen(self.sys)
+    }
+
+    /// Sets the value of `TCP_USER_TIMEOUT` on this socket.
+    ///
+    /// This is the maximum amount of time transmitted data may remain
+    /// unacknowledged, or buffered data may remain untransmitted due to a zero
+    /// window, before the connection is forcibly closed with an error of kind
+    /// [`TimedOut`]. This allows dead peers to be detected well before the
+    /// retransmission timeout expires (which can take over 15 minutes). When
+    /// used together with TCP keepalive it also overrides the keepalive
+    /// settings for how long a connection may go without a response to
+    /// keepalive probes.
+    ///
+    /// A timeout of zero means the system default is used. The timeout has
+    /// millisecond granularity.
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        sys::tcp::set_user_timeout(self.sys, timeout)
+    }
+
+    /// Get the value of `TCP_USER_TIMEOUT` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_user_timeout(&self) -> io::Result<Duration> {
+        sys::tcp::get_user_timeout(self.sys)
+    }
+
+    /// Sets the value of `TCP_NOTSENT_LOWAT` on this socket.
+    ///
+    /// This limits the amount of data in the send buffer that hasn't been
+    /// sent yet to (roughly) `lowat` bytes, instead of filling the entire send
+    /// buffer. Keeping the unsent backlog small reduces the latency of newly
+    /// written data and the memory used by the socket.
+    ///
+    /// # Interaction with `Interest::WRITABLE`
+    ///
+    /// This changes when the socket is considered writable. Writes return a
+    /// [`WouldBlock`] error once the amount of unsent data exceeds `lowat`,
+    /// even if there is space left in the send buffer. A [writable event] is
+    /// only returned once the amount of unsent data drops below `lowat` again;
+    /// the kernel may wait until it drops further to avoid frequent wake ups.
+    /// As always, after receiving a writable event the socket must be written
+    /// to until a `WouldBlock` error is returned, otherwise no further
+    /// writable event may be returned.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [writable event]: crate::event::Event::is_writable
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        sys::tcp::set_notsent_lowat(self.sys, lowat)
+    }
+
+    /// Get the value of `TCP_NOTSENT_LOWAT` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_notsent_lowat(&self) -> io::Result<u32> {
+        sys::tcp::get_notsent_lowat(self.sys)
+    }
+
+    /// Sets the value of `SO_BINDTODEVICE` on this socket, binding it to the
+    /// network interface named `interface`, e.g. `b"eth0"`.
+    ///
+    /// Only packets received on the interface are processed by the socket and
+    /// packets sent by the socket are sent on the interface, regardless of the
+    /// routing table. An empty name removes the binding.
+    ///
+    /// Changing the binding of a socket that is already bound to an interface
+    /// requires the `CAP_NET_RAW` capability (before Linux 5.7 binding always
+    /// does), without it an error of kind [`PermissionDenied`] is returned.
+    ///
+    /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_device(&self, interface: &[u8]) -> io::Result<()> {
+        sys::tcp::set_bind_device(self.sys, interface)
+    }
+
+    /// Gets the value of `SO_BINDTODEVICE` on this socket, returning `None`
+    /// if the socket isn't bound to an interface.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_device(&self) -> io::Result<Option<Vec<u8>>> {
+        sys::tcp::get_bind_device(self.sys)
+    }
+
+    /// Sets the value of `SO_MARK` on this socket.
+    ///
+    /// The mark is set on all packets sent by the socket and can be used by
+    /// routing policies (`ip rule`) or netfilter rules, e.g. to use a
+    /// different routing table. Setting the mark requires the `CAP_NET_ADMIN`
+    /// capability, without it an error of kind [`PermissionDenied`] is
+    /// returned.
+    ///
+    /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        sys::tcp::set_mark(self.sys, mark)
+    }
+
+    /// Gets the value of `SO_MARK` on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_mark(&self) -> io::Result<u32> {
+        sys::tcp::get_mark(self.sys)
+    }
+
+    /// Sets the value of `TCP_CONGESTION` on this socket, selecting the
+    /// congestion control algorithm, e.g. `"cubic"` or `"bbr"`.
+    ///
+    /// The available algorithms are listed in
+    /// `/proc/sys/net/ipv4/tcp_available_congestion_control`. If the algorithm
+    /// isn't available an error of kind [`NotFound`] is returned. Without the
+    /// `CAP_NET_ADMIN` capability only the algorithms listed in
+    /// `/proc/sys/net/ipv4/tcp_allowed_congestion_control` may be selected,
+    /// other algorithms return an error of kind [`PermissionDenied`].
+    ///
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_congestion_control(&self, name: &str) -> io::Result<()> {
+        sys::tcp::set_congestion_control(self.sys, name)
+    }
+
+    /// Get the value of `TCP_CONGESTION` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_congestion_control(&self) -> io::Result<String> {
+        sys::tcp::get_congestion_control(self.sys)
+    }
+
+    /// Sets the value of `IP_BIND_ADDRESS_NO_PORT` on this socket.
+    ///
+    /// When set, binding the socket to an address with port zero doesn't
+    /// select the local port yet, it's selected by `connect` instead. This
+    /// allows the same local port to be used for connections to different
+    /// remote addresses, avoiding ephemeral port exhaustion when many
+    /// connections are bound to the same local address. Must be set before
+    /// [`bind`].
+    ///
+    /// [`bind`]: TcpSocket::bind
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_address_no_port(&self, no_port: bool) -> io::Result<()> {
+        sys::tcp::set_bind_address_no_port(self.sys, no_port)
+    }
+
+    /// Get the value of `IP_BIND_ADDRESS_NO_PORT` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_bind_address_no_port(&self) -> io::Result<bool> {
+        sys::tcp::get_bind_address_no_port(self.sys)
+    }
+
+    /// Sets the value of `IP_LOCAL_PORT_RANGE` on this socket.
+    ///
+    /// Restricts the ephemeral ports selected for this socket to `low..=high`,
+    /// which must be within the `net.ipv4.ip_local_port_range` sysctl. A bound
+    /// of zero means the bound of the sysctl is used. Requires Linux 6.3 or
+    /// later.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_local_port_range(&self, low: u16, high: u16) -> io::Result<()> {
+        sys::tcp::set_local_port_range(self.sys, low, high)
+    }
+
+    /// Get the value of `IP_LOCAL_PORT_RANGE` set on this socket, as
+    /// `(low, high)`.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_local_port_range(&self) -> io::Result<(u16, u16)> {
+        sys::tcp::get_local_port_range(self.sys)
+    }
+
     /// Returns the local address of this socket
     ///
     /// Will return `Err` result in windows if called before calling `bind`
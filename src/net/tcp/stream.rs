@@ -5,10 +5,19 @@ use std::net::{self, Shutdown, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
+use std::time::Duration;
 
 use crate::io_source::IoSource;
-use crate::net::TcpSocket;
-use crate::{event, Interest, Registry, Token};
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use crate::net::TcpInfo;
+use crate::net::{TcpKeepalive, TcpSocket};
+use crate::{event, sys, Interest, Registry, Token};
 
 /// A non-blocking TCP stream between a local socket and a remote socket.
 ///
@@ -54,6 +63,25 @@ impl TcpStream {
         socket.connect(addr)
     }
 
+    /// Create a new TCP stream bound to the `local` address and issue a
+    /// non-blocking connect to the `remote` address.
+    ///
+    /// If the port of `local` is zero, `IP_BIND_ADDRESS_NO_PORT` is set on
+    /// Linux (see [`TcpSocket::set_bind_address_no_port`]), so that the local
+    /// port is only selected when connecting. This allows many connections
+    /// from the same local address without exhausting the ephemeral ports.
+    pub fn connect_from(local: SocketAddr, remote: SocketAddr) -> io::Result<TcpStream> {
+        let socket = TcpSocket::new_for_addr(remote)?;
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if local.port() == 0 {
+                socket.set_bind_address_no_port(true)?;
+            }
+        }
+        socket.bind(local)?;
+        socket.connect(remote)
+    }
+
     /// Creates a new `TcpStream` from a standard `net::TcpStream`.
     ///
     /// This function is intended to be used to wrap a TCP stream from the
@@ -170,6 +198,252 @@ impl TcpStream {
     pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
         self.inner.peek(buf)
     }
+
+    /// Sets the value of `SO_LINGER` on this socket.
+    ///
+    /// See [`TcpSocket::set_linger`].
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        sys::tcp::set_linger(self.sys(), dur)
+    }
+
+    /// Gets the value of `SO_LINGER` on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_linger(self.sys())
+    }
+
+    /// Sets the value of `SO_RCVBUF` on this socket.
+    pub fn set_recv_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_recv_buffer_size(self.sys(), size)
+    }
+
+    /// Gets the value of `SO_RCVBUF` set on this socket.
+    ///
+    /// The returned value may differ from the value passed to
+    /// [`set_recv_buffer_size`], see [`TcpSocket::get_recv_buffer_size`].
+    ///
+    /// [`set_recv_buffer_size`]: TcpStream::set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_recv_buffer_size(self.sys())
+    }
+
+    /// Sets the value of `SO_SNDBUF` on this socket.
+    pub fn set_send_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::tcp::set_send_buffer_size(self.sys(), size)
+    }
+
+    /// Gets the value of `SO_SNDBUF` set on this socket.
+    ///
+    /// The returned value may differ from the value passed to
+    /// [`set_send_buffer_size`], see [`TcpSocket::get_send_buffer_size`].
+    ///
+    /// [`set_send_buffer_size`]: TcpStream::set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<u32> {
+        sys::tcp::get_send_buffer_size(self.sys())
+    }
+
+    /// Sets whether keepalive messages are enabled to be sent on this socket.
+    ///
+    /// This will set the `SO_KEEPALIVE` option on this socket.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        sys::tcp::set_keepalive(self.sys(), keepalive)
+    }
+
+    /// Returns whether or not TCP keepalive probes will be sent by this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        sys::tcp::get_keepalive(self.sys())
+    }
+
+    /// Sets parameters configuring TCP keepalive probes for this socket,
+    /// enabling TCP keepalive.
+    ///
+    /// See [`TcpSocket::set_keepalive_params`].
+    pub fn set_keepalive_params(&self, keepalive: TcpKeepalive) -> io::Result<()> {
+        self.set_keepalive(true)?;
+        sys::tcp::set_keepalive_params(self.sys(), keepalive)
+    }
+
+    /// Returns the amount of time after which TCP keepalive probes will be sent
+    /// on idle connections, or `None` if keepalive messages are disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_time`].
+    #[cfg_attr(docsrs, doc(cfg(not(target_os = "windows"))))]
+    #[cfg(not(target_os = "windows"))]
+    pub fn keepalive_time(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_keepalive_time(self.sys())
+    }
+
+    /// Returns the time interval between TCP keepalive probes, or `None` if
+    /// keepalive messages are disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_interval`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        )))
+    )]
+    #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+    pub fn keepalive_interval(&self) -> io::Result<Option<Duration>> {
+        sys::tcp::get_keepalive_interval(self.sys())
+    }
+
+    /// Returns the maximum number of TCP keepalive probes that will be sent
+    /// before dropping a connection, or `None` if keepalive messages are
+    /// disabled.
+    ///
+    /// See [`TcpSocket::get_keepalive_retries`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        )))
+    )]
+    #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+    pub fn keepalive_retries(&self) -> io::Result<Option<u32>> {
+        sys::tcp::get_keepalive_retries(self.sys())
+    }
+
+    /// Sets the value of `TCP_USER_TIMEOUT` on this socket.
+    ///
+    /// See [`TcpSocket::set_user_timeout`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        sys::tcp::set_user_timeout(self.sys(), timeout)
+    }
+
+    /// Gets the value of `TCP_USER_TIMEOUT` on this socket.
+    ///
+    /// See [`TcpSocket::get_user_timeout`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn user_timeout(&self) -> io::Result<Duration> {
+        sys::tcp::get_user_timeout(self.sys())
+    }
+
+    /// Sets the value of `TCP_NOTSENT_LOWAT` on this socket.
+    ///
+    /// This changes when the stream is reported as writable, see
+    /// [`TcpSocket::set_notsent_lowat`] for details.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        sys::tcp::set_notsent_lowat(self.sys(), lowat)
+    }
+
+    /// Gets the value of `TCP_NOTSENT_LOWAT` on this socket.
+    ///
+    /// See [`TcpSocket::get_notsent_lowat`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        sys::tcp::get_notsent_lowat(self.sys())
+    }
+
+    /// Sets the value of `TCP_CONGESTION` on this socket.
+    ///
+    /// See [`TcpSocket::set_congestion_control`].
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_congestion_control(&self, name: &str) -> io::Result<()> {
+        sys::tcp::set_congestion_control(self.sys(), name)
+    }
+
+    /// Gets the value of `TCP_CONGESTION` on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn congestion_control(&self) -> io::Result<String> {
+        sys::tcp::get_congestion_control(self.sys())
+    }
+
+    /// Returns `true` if the connection uses Multipath TCP (MPTCP).
+    ///
+    /// Returns `false` for plain TCP connections, including connections
+    /// created using an MPTCP socket (see [`TcpSocket::new_mptcp_v4`]) that
+    /// fell back to plain TCP because the peer doesn't support MPTCP. Requires
+    /// Linux 5.16 or later, older versions return an error (`ENOPROTOOPT`).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn is_mptcp(&self) -> io::Result<bool> {
+        sys::tcp::get_is_mptcp(self.sys())
+    }
+
+    /// Returns statistics of the connection, such as the round trip time and
+    /// congestion window, using `getsockopt(TCP_INFO)`.
+    ///
+    /// This uses `TCP_CONNECTION_INFO` on macOS and iOS, see [`TcpInfo`] for
+    /// the statistics reported per platform.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        )))
+    )]
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        sys::tcp::get_tcp_info(self.sys())
+    }
+
+    #[cfg(unix)]
+    fn sys(&self) -> sys::tcp::TcpSocket {
+        self.inner.as_raw_fd()
+    }
+
+    #[cfg(windows)]
+    fn sys(&self) -> sys::tcp::TcpSocket {
+        self.inner.as_raw_socket() as _
+    }
 }
 
 impl Read for TcpStream {
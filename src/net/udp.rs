@@ -13,11 +13,15 @@ use crate::{event, sys, Interest, Registry, Token};
 use std::fmt;
 use std::io;
 use std::net;
-use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+use std::sync::atomic::{AtomicI32, Ordering};
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 #[cfg(windows)]
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::time::Duration;
 
 /// A User Datagram Protocol socket.
 ///
@@ -90,6 +94,103 @@ use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket}
 /// ```
 pub struct UdpSocket {
     inner: IoSource<net::UdpSocket>,
+    /// Raw OS error hit by `recv_many` after some datagrams were already
+    /// received, returned by the next call (0 if none). Unlike the errors of
+    /// `send_many`, which are returned again when sending the remaining
+    /// datagrams, it could otherwise be lost, e.g. a pending `ECONNREFUSED`.
+    /// On Linux and Android the kernel does this for `recvmmsg(2)`.
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    recv_many_error: AtomicI32,
+}
+
+/// Metadata of a datagram received using [`UdpSocket::recv_many`].
+#[derive(Copy, Clone, Debug)]
+pub struct RecvMeta {
+    pub(crate) len: usize,
+    pub(crate) addr: SocketAddr,
+    pub(crate) truncated: bool,
+    pub(crate) segment_size: Option<usize>,
+    pub(crate) dst_addr: Option<IpAddr>,
+    pub(crate) interface_index: Option<u32>,
+}
+
+impl RecvMeta {
+    /// Returns new metadata, to be filled by [`UdpSocket::recv_many`].
+    pub fn new() -> RecvMeta {
+        RecvMeta {
+            len: 0,
+            addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            truncated: false,
+            segment_size: None,
+            dst_addr: None,
+            interface_index: None,
+        }
+    }
+
+    /// Returns the number of bytes received into the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the datagram was empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the address the datagram was received from.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns `true` if the datagram was larger than the buffer, in which
+    /// case the excess data is discarded.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the size of the datagrams coalesced into the buffer if generic
+    /// receive offload is enabled (see [`UdpSocket::set_gro`]), or `None` if
+    /// the buffer holds a single datagram.
+    ///
+    /// The buffer holds `len / segment_size` datagrams of `segment_size`
+    /// bytes, possibly followed by a single smaller datagram.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn segment_size(&self) -> Option<usize> {
+        self.segment_size
+    }
+
+    /// Returns the destination address of the datagram, i.e. the local
+    /// address it was received on, if packet info is enabled (see
+    /// [`UdpSocket::set_recv_pktinfo`]).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn dst_addr(&self) -> Option<IpAddr> {
+        self.dst_addr
+    }
+
+    /// Returns the index of the interface the datagram was received on, if
+    /// packet info is enabled (see [`UdpSocket::set_recv_pktinfo`]).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn interface_index(&self) -> Option<u32> {
+        self.interface_index
+    }
+}
+
+impl Default for RecvMeta {
+    fn default() -> RecvMeta {
+        RecvMeta::new()
+    }
 }
 
 impl UdpSocket {
@@ -131,6 +232,8 @@ impl UdpSocket {
     pub fn from_std(socket: net::UdpSocket) -> UdpSocket {
         UdpSocket {
             inner: IoSource::new(socket),
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            recv_many_error: AtomicI32::new(0),
         }
     }
 
@@ -247,6 +350,208 @@ impl UdpSocket {
         self.inner.do_io(|inner| inner.recv_from(buf))
     }
 
+    /// Receives multiple datagrams from the socket in a single call.
+    ///
+    /// Datagram `i` is received into `bufs[i]`, its length, source address and
+    /// whether it was truncated are stored in `meta[i]`. Returns the number of
+    /// datagrams received, at most the length of the shorter of `bufs` and
+    /// `meta`. Both can be reused for the next call.
+    ///
+    /// This uses `recvmmsg(2)` on Linux and Android, receiving at most 32
+    /// datagrams per call, other platforms call `recvmsg(2)` or `recv_from`
+    /// in a loop. If no datagram is available a [`WouldBlock`] error is
+    /// returned; as with [`recv_from`] the socket must be read from until it
+    /// returns `WouldBlock` before a new readable event is returned. Other
+    /// errors hit after receiving some datagrams are returned by the next
+    /// call.
+    ///
+    /// # Notes
+    ///
+    /// On Windows truncated datagrams return a `WSAEMSGSIZE` error instead,
+    /// see [`recv_from`].
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`recv_from`]: UdpSocket::recv_from
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// use mio::net::{RecvMeta, UdpSocket};
+    ///
+    /// let socket = UdpSocket::bind("127.0.0.1:0".parse()?)?;
+    ///
+    /// let mut bufs = vec![[0; 1500]; 32];
+    /// let mut meta = vec![RecvMeta::new(); 32];
+    /// let n = socket.recv_many(&mut bufs, &mut meta)?;
+    /// for (buf, meta) in bufs.iter().zip(meta.iter()).take(n) {
+    ///     println!("Received {:?} from {}", &buf[..meta.len()], meta.addr());
+    /// }
+    /// #
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn recv_many<B: AsMut<[u8]>>(
+        &self,
+        bufs: &mut [B],
+        meta: &mut [RecvMeta],
+    ) -> io::Result<usize> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            self.inner
+                .do_io(|inner| sys::udp::recv_many(inner, bufs, meta))
+        }
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        {
+            if self.recv_many_error.load(Ordering::Relaxed) != 0 {
+                let code = self.recv_many_error.swap(0, Ordering::Relaxed);
+                if code != 0 {
+                    return Err(io::Error::from_raw_os_error(code));
+                }
+            }
+
+            let mut received = 0;
+            for (buf, meta) in bufs.iter_mut().zip(meta.iter_mut()) {
+                match self
+                    .inner
+                    .do_io(|inner| sys::udp::recv_msg(inner, buf.as_mut(), meta))
+                {
+                    Ok(()) => received += 1,
+                    Err(err) if received == 0 => return Err(err),
+                    // No more datagrams available (for now), return the
+                    // datagrams received so far.
+                    Err(ref err)
+                        if err.kind() == io::ErrorKind::WouldBlock
+                            || err.kind() == io::ErrorKind::Interrupted =>
+                    {
+                        break
+                    }
+                    // Return the datagrams received so far and the error in
+                    // the next call. Errors not returned by the OS concern
+                    // the datagram itself, e.g. an unsupported address.
+                    Err(err) => {
+                        if let Some(code) = err.raw_os_error() {
+                            self.recv_many_error.store(code, Ordering::Relaxed);
+                        }
+                        break;
+                    }
+                }
+            }
+            Ok(received)
+        }
+    }
+
+    /// Receives a single datagram on the socket using `recvmsg(2)`.
+    ///
+    /// Unlike [`recv_from`] this returns the datagram's metadata, including
+    /// whether it was truncated and, if generic receive offload is enabled
+    /// (see [`set_gro`]), the size of the datagrams coalesced into `buf`.
+    ///
+    /// [`recv_from`]: UdpSocket::recv_from
+    /// [`set_gro`]: UdpSocket::set_gro
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[cfg(unix)]
+    pub fn recv_msg(&self, buf: &mut [u8]) -> io::Result<RecvMeta> {
+        let mut meta = RecvMeta::new();
+        self.inner
+            .do_io(|inner| sys::udp::recv_msg(inner, buf, &mut meta))?;
+        Ok(meta)
+    }
+
+    /// Sends multiple datagrams on the socket in a single call.
+    ///
+    /// Datagram `bufs[i]` is sent to `addrs[i]`. Returns the number of
+    /// datagrams sent, which may be less than the number of datagrams given.
+    /// Returns an [`InvalidInput`] error if `bufs` and `addrs` don't have the
+    /// same length.
+    ///
+    /// This uses `sendmmsg(2)` on Linux and Android, sending at most 32
+    /// datagrams per call, other platforms call `send_to` in a loop. If no
+    /// datagram could be sent a [`WouldBlock`] error is returned. If an error
+    /// is hit after sending some datagrams the number of datagrams sent is
+    /// returned, sending the remaining datagrams usually returns the error.
+    ///
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    pub fn send_many<B: AsRef<[u8]>>(&self, bufs: &[B], addrs: &[SocketAddr]) -> io::Result<usize> {
+        if bufs.len() != addrs.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "number of buffers and addresses differ",
+            ));
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            self.inner
+                .do_io(|inner| sys::udp::send_many(inner, bufs, addrs))
+        }
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        {
+            let mut sent = 0;
+            for (buf, addr) in bufs.iter().zip(addrs.iter()) {
+                match self.inner.do_io(|inner| inner.send_to(buf.as_ref(), addr)) {
+                    Ok(_) => sent += 1,
+                    Err(err) if sent == 0 => return Err(err),
+                    // Return the number of datagrams sent so far, the error
+                    // is returned again when the remaining datagrams are
+                    // sent, as with `sendmmsg(2)`.
+                    Err(_) => break,
+                }
+            }
+            Ok(sent)
+        }
+    }
+
+    /// Sends `buf` to `target` as multiple datagrams of `segment_size` bytes
+    /// using UDP generic segmentation offload (`UDP_SEGMENT`).
+    ///
+    /// The kernel, or the network card, splits `buf` into datagrams of
+    /// `segment_size` bytes, the last datagram may be smaller. This is a lot
+    /// cheaper than sending each datagram separately. `buf` may hold at most
+    /// 64 segments and must fit in a single IP packet (about 64 KB).
+    ///
+    /// Requires Linux 4.18 or later, use [`supports_gso`] to check whether
+    /// the running kernel supports it.
+    ///
+    /// [`supports_gso`]: UdpSocket::supports_gso
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn send_segmented(
+        &self,
+        buf: &[u8],
+        segment_size: u16,
+        target: SocketAddr,
+    ) -> io::Result<usize> {
+        self.inner
+            .do_io(|inner| sys::udp::send_segmented(inner, buf, segment_size, target))
+    }
+
+    /// Sends data on the socket to the given address, using `source` as
+    /// source address of the datagram.
+    ///
+    /// This is useful for sockets bound to the unspecified address
+    /// (`0.0.0.0` or `::`) on hosts with multiple addresses, where replies
+    /// should be sent from the address the request was received on, see
+    /// [`RecvMeta::dst_addr`]. `source` must be a local address and use the
+    /// same address family as the socket.
+    ///
+    /// This uses `sendmsg(2)` with an `IP_PKTINFO` or `IPV6_PKTINFO` control
+    /// message.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn send_msg(&self, buf: &[u8], target: SocketAddr, source: IpAddr) -> io::Result<usize> {
+        self.inner
+            .do_io(|inner| sys::udp::send_msg(inner, buf, target, source))
+    }
+
     /// Receives data from the socket, without removing it from the input queue.
     /// On success, returns the number of bytes read and the address from whence
     /// the data came.
@@ -540,6 +845,203 @@ impl UdpSocket {
         sys::udp::only_v6(&self.inner)
     }
 
+    /// Sets the value of the `SO_BUSY_POLL` option on this socket.
+    ///
+    /// This sets the approximate time to busy poll the network device queue on
+    /// blocking receives, or when the socket is polled using [`Poll`] with
+    /// busy polling enabled (see [`Poll::set_busy_poll`]). Increasing the value
+    /// beyond the `net.core.busy_read` sysctl requires the `CAP_NET_ADMIN`
+    /// capability.
+    ///
+    /// [`Poll`]: crate::Poll
+    /// [`Poll::set_busy_poll`]: crate::Poll::set_busy_poll
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_busy_poll(&self, dur: Duration) -> io::Result<()> {
+        sys::udp::set_busy_poll(&self.inner, dur)
+    }
+
+    /// Gets the value of the `SO_BUSY_POLL` option on this socket.
+    ///
+    /// For more information about this option, see [`set_busy_poll`].
+    ///
+    /// [`set_busy_poll`]: UdpSocket::set_busy_poll
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn busy_poll(&self) -> io::Result<Duration> {
+        sys::udp::busy_poll(&self.inner)
+    }
+
+    /// Sets the value of the `SO_PREFER_BUSY_POLL` option on this socket.
+    ///
+    /// When set the kernel prefers busy polling over softirq processing for
+    /// the network device queue of this socket. Requires Linux 5.11 or later.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_prefer_busy_poll(&self, prefer: bool) -> io::Result<()> {
+        sys::udp::set_prefer_busy_poll(&self.inner, prefer)
+    }
+
+    /// Gets the value of the `SO_PREFER_BUSY_POLL` option on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn prefer_busy_poll(&self) -> io::Result<bool> {
+        sys::udp::prefer_busy_poll(&self.inner)
+    }
+
+    /// Sets the value of `SO_BINDTODEVICE` on this socket, binding it to the
+    /// network interface named `interface`, e.g. `b"eth0"`.
+    ///
+    /// Only packets received on the interface are processed by the socket and
+    /// packets sent by the socket are sent on the interface, regardless of the
+    /// routing table. An empty name removes the binding.
+    ///
+    /// Changing the binding of a socket that is already bound to an interface
+    /// requires the `CAP_NET_RAW` capability (before Linux 5.7 binding always
+    /// does), without it an error of kind [`PermissionDenied`] is returned.
+    ///
+    /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_device(&self, interface: &[u8]) -> io::Result<()> {
+        sys::udp::bind_device(&self.inner, interface)
+    }
+
+    /// Gets the value of `SO_BINDTODEVICE` on this socket, returning `None`
+    /// if the socket isn't bound to an interface.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
+        sys::udp::device(&self.inner)
+    }
+
+    /// Sets the value of `SO_MARK` on this socket.
+    ///
+    /// The mark is set on all packets sent by the socket and can be used by
+    /// routing policies (`ip rule`) or netfilter rules, e.g. to use a
+    /// different routing table. Setting the mark requires the `CAP_NET_ADMIN`
+    /// capability, without it an error of kind [`PermissionDenied`] is
+    /// returned.
+    ///
+    /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        sys::udp::set_mark(&self.inner, mark)
+    }
+
+    /// Gets the value of `SO_MARK` on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mark(&self) -> io::Result<u32> {
+        sys::udp::mark(&self.inner)
+    }
+
+    /// Returns `true` if the running kernel supports UDP generic segmentation
+    /// offload, i.e. [`send_segmented`].
+    ///
+    /// [`send_segmented`]: UdpSocket::send_segmented
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn supports_gso(&self) -> io::Result<bool> {
+        sys::udp::supports_gso(&self.inner)
+    }
+
+    /// Sets the value of the `UDP_GRO` option on this socket.
+    ///
+    /// If enabled the kernel may coalesce multiple received datagrams from
+    /// the same source into a single buffer. Use [`recv_msg`] to receive them,
+    /// [`RecvMeta::segment_size`] reports the size of the coalesced datagrams
+    /// so they can be split again. The buffer should be large enough to hold
+    /// the coalesced datagrams (64 KB), otherwise they are truncated.
+    ///
+    /// Requires Linux 5.0 or later, older kernels return an error.
+    ///
+    /// [`recv_msg`]: UdpSocket::recv_msg
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_gro(&self, gro: bool) -> io::Result<()> {
+        sys::udp::set_gro(&self.inner, gro)
+    }
+
+    /// Gets the value of the `UDP_GRO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_gro`].
+    ///
+    /// [`set_gro`]: UdpSocket::set_gro
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn gro(&self) -> io::Result<bool> {
+        sys::udp::gro(&self.inner)
+    }
+
+    /// Sets whether the destination address and interface of received
+    /// datagrams are reported by [`recv_msg`] and [`recv_many`].
+    ///
+    /// This sets the `IP_PKTINFO` option on IPv4 sockets and the
+    /// `IPV6_RECVPKTINFO` option on IPv6 sockets, for which the destination
+    /// of IPv4 datagrams is reported as IPv4-mapped IPv6 address. See
+    /// [`RecvMeta::dst_addr`] and [`RecvMeta::interface_index`].
+    ///
+    /// [`recv_msg`]: UdpSocket::recv_msg
+    /// [`recv_many`]: UdpSocket::recv_many
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_recv_pktinfo(&self, pktinfo: bool) -> io::Result<()> {
+        sys::udp::set_recv_pktinfo(&self.inner, pktinfo)
+    }
+
+    /// Gets whether the destination address and interface of received
+    /// datagrams are reported.
+    ///
+    /// For more information about this option, see [`set_recv_pktinfo`].
+    ///
+    /// [`set_recv_pktinfo`]: UdpSocket::set_recv_pktinfo
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(target_os = "android", target_os = "linux")))
+    )]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn recv_pktinfo(&self) -> io::Result<bool> {
+        sys::udp::recv_pktinfo(&self.inner)
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
@@ -0,0 +1,249 @@
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
+
+use crate::net::UdpSocket;
+use crate::sys;
+
+/// A non-blocking UDP socket used to configure a socket before binding it.
+///
+/// [`UdpSocket::bind`] creates and binds a socket in one step, `UdpSocketBuilder`
+/// allows options that must be set before binding, such as `SO_REUSEPORT` or
+/// `IPV6_V6ONLY`, to be set first. It's the UDP equivalent of [`TcpSocket`].
+///
+/// The socket will be closed when the value is dropped.
+///
+/// [`TcpSocket`]: crate::net::TcpSocket
+///
+/// # Examples
+///
+#[cfg_attr(feature = "os-poll", doc = "```")]
+#[cfg_attr(not(feature = "os-poll"), doc = "```ignore")]
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::UdpSocketBuilder;
+///
+/// // Only receive IPv6 datagrams, regardless of the system default.
+/// let builder = UdpSocketBuilder::new_v6()?;
+/// builder.set_only_v6(true)?;
+/// let socket = builder.bind("[::1]:0".parse()?)?;
+/// assert!(socket.only_v6()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UdpSocketBuilder {
+    sys: sys::udp::UdpSocket,
+}
+
+impl UdpSocketBuilder {
+    /// Create a new IPv4 UDP socket.
+    ///
+    /// This calls `socket(2)`.
+    pub fn new_v4() -> io::Result<UdpSocketBuilder> {
+        sys::udp::new_v4_socket().map(|sys| UdpSocketBuilder { sys })
+    }
+
+    /// Create a new IPv6 UDP socket.
+    ///
+    /// This calls `socket(2)`.
+    pub fn new_v6() -> io::Result<UdpSocketBuilder> {
+        sys::udp::new_v6_socket().map(|sys| UdpSocketBuilder { sys })
+    }
+
+    /// Bind the socket to `addr`, converting it to a `UdpSocket`.
+    pub fn bind(self, addr: SocketAddr) -> io::Result<UdpSocket> {
+        let socket = sys::udp::bind_socket(self.sys, addr)?;
+
+        // Don't close the socket
+        mem::forget(self);
+        Ok(UdpSocket::from_std(socket))
+    }
+
+    /// Connect the socket to `addr`, converting it to a `UdpSocket`.
+    ///
+    /// The socket is bound to an unspecified address and a random port by the
+    /// operating system. To use a specific local address, use [`bind`]
+    /// followed by [`UdpSocket::connect`].
+    ///
+    /// [`bind`]: UdpSocketBuilder::bind
+    pub fn connect(self, addr: SocketAddr) -> io::Result<UdpSocket> {
+        let socket = sys::udp::connect_socket(self.sys, addr)?;
+
+        // Don't close the socket
+        mem::forget(self);
+        Ok(UdpSocket::from_std(socket))
+    }
+
+    /// Sets the value of `SO_REUSEADDR` on this socket.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        sys::udp::set_reuseaddr(self.sys, reuseaddr)
+    }
+
+    /// Get the value of `SO_REUSEADDR` set on this socket.
+    pub fn get_reuseaddr(&self) -> io::Result<bool> {
+        sys::udp::get_reuseaddr(self.sys)
+    }
+
+    /// Sets the value of `SO_REUSEPORT` on this socket, allowing multiple
+    /// sockets to bind to the same address.
+    ///
+    /// On Linux datagrams are distributed over all sockets bound to the
+    /// address, other platforms deliver unicast datagrams to a single socket
+    /// only.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))
+    )]
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        sys::udp::set_reuseport(self.sys, reuseport)
+    }
+
+    /// Get the value of `SO_REUSEPORT` set on this socket.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))
+    )]
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub fn get_reuseport(&self) -> io::Result<bool> {
+        sys::udp::get_reuseport(self.sys)
+    }
+
+    /// Sets the value of `SO_RCVBUF` on this socket.
+    pub fn set_recv_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::udp::set_recv_buffer_size(self.sys, size)
+    }
+
+    /// Get the value of `SO_RCVBUF` set on this socket.
+    ///
+    /// See [`TcpSocket::get_recv_buffer_size`] for the relation between the
+    /// value set and the value returned.
+    ///
+    /// [`TcpSocket::get_recv_buffer_size`]: crate::net::TcpSocket::get_recv_buffer_size
+    pub fn get_recv_buffer_size(&self) -> io::Result<u32> {
+        sys::udp::get_recv_buffer_size(self.sys)
+    }
+
+    /// Sets the value of `SO_SNDBUF` on this socket.
+    pub fn set_send_buffer_size(&self, size: u32) -> io::Result<()> {
+        sys::udp::set_send_buffer_size(self.sys, size)
+    }
+
+    /// Get the value of `SO_SNDBUF` set on this socket.
+    ///
+    /// See [`TcpSocket::get_send_buffer_size`] for the relation between the
+    /// value set and the value returned.
+    ///
+    /// [`TcpSocket::get_send_buffer_size`]: crate::net::TcpSocket::get_send_buffer_size
+    pub fn get_send_buffer_size(&self) -> io::Result<u32> {
+        sys::udp::get_send_buffer_size(self.sys)
+    }
+
+    /// Sets the value of `SO_BROADCAST` on this socket, allowing datagrams to
+    /// be sent to a broadcast address.
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        sys::udp::set_broadcast(self.sys, broadcast)
+    }
+
+    /// Get the value of `SO_BROADCAST` set on this socket.
+    pub fn get_broadcast(&self) -> io::Result<bool> {
+        sys::udp::get_broadcast(self.sys)
+    }
+
+    /// Sets the value of `IPV6_V6ONLY` on this socket.
+    ///
+    /// If set to `true` the socket only communicates using IPv6, e.g. a socket
+    /// bound to `[::]` doesn't receive IPv4 datagrams. If set to `false` it
+    /// also communicates with IPv4 peers using IPv4-mapped IPv6 addresses. The
+    /// default depends on the platform (on Linux, the `net.ipv6.bindv6only`
+    /// sysctl), setting it explicitly makes the behaviour the same everywhere.
+    ///
+    /// Only valid for IPv6 sockets.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        sys::udp::set_only_v6(self.sys, only_v6)
+    }
+
+    /// Get the value of `IPV6_V6ONLY` set on this socket.
+    pub fn get_only_v6(&self) -> io::Result<bool> {
+        sys::udp::get_only_v6(self.sys)
+    }
+}
+
+impl Drop for UdpSocketBuilder {
+    fn drop(&mut self) {
+        sys::udp::close(self.sys);
+    }
+}
+
+#[cfg(unix)]
+impl IntoRawFd for UdpSocketBuilder {
+    fn into_raw_fd(self) -> RawFd {
+        let ret = self.sys;
+        // Avoid closing the socket
+        mem::forget(self);
+        ret
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for UdpSocketBuilder {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys
+    }
+}
+
+#[cfg(unix)]
+impl FromRawFd for UdpSocketBuilder {
+    /// Converts a `RawFd` to a `UdpSocketBuilder`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the socket is in
+    /// non-blocking mode.
+    unsafe fn from_raw_fd(fd: RawFd) -> UdpSocketBuilder {
+        UdpSocketBuilder { sys: fd }
+    }
+}
+
+#[cfg(windows)]
+impl IntoRawSocket for UdpSocketBuilder {
+    fn into_raw_socket(self) -> RawSocket {
+        // The winapi crate defines `SOCKET` as `usize`. The Rust std
+        // conditionally defines `RawSocket` as a fixed size unsigned integer
+        // matching the pointer width. These end up being the same type but we
+        // must cast between them.
+        let ret = self.sys as RawSocket;
+
+        // Avoid closing the socket
+        mem::forget(self);
+
+        ret
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for UdpSocketBuilder {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.sys as RawSocket
+    }
+}
+
+#[cfg(windows)]
+impl FromRawSocket for UdpSocketBuilder {
+    /// Converts a `RawSocket` to a `UdpSocketBuilder`.
+    ///
+    /// # Notes
+    ///
+    /// The caller is responsible for ensuring that the socket is in
+    /// non-blocking mode.
+    unsafe fn from_raw_socket(socket: RawSocket) -> UdpSocketBuilder {
+        UdpSocketBuilder {
+            sys: socket as sys::udp::UdpSocket,
+        }
+    }
+}
@@ -1,9 +1,14 @@
+#[cfg(feature = "os-poll")]
+use crate::event::record::Recorder;
 use crate::{event, sys, Events, Interest, Token};
-use log::trace;
+use log::{trace, warn};
+#[cfg(feature = "os-poll")]
+use std::io::Write;
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, RawFd};
-use std::time::Duration;
-use std::{fmt, io};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{cmp, fmt, io};
 
 /// Polls for readiness events on all registered values.
 ///
@@ -181,6 +186,9 @@ use std::{fmt, io};
 /// | iOS           | [kqueue]  |
 /// | macOS         | [kqueue]  |
 ///
+/// On Android and Linux a `poll(2)` based selector can be used instead of
+/// epoll, see `Poll::with_backend`.
+///
 /// On all supported platforms, socket operations are handled by using the
 /// system selector. Platform specific extensions (e.g. [`SourceFd`]) allow
 /// accessing other features provided by individual system selectors. For
@@ -203,11 +211,50 @@ use std::{fmt, io};
 /// [`Poll::poll`]: struct.Poll.html#method.poll
 pub struct Poll {
     registry: Registry,
+    /// Window to busy poll for before blocking, see [`Poll::set_busy_poll`].
+    busy_poll: Option<Duration>,
+    /// See [`Poll::record`].
+    #[cfg(feature = "os-poll")]
+    recorder: Option<Recorder>,
+}
+
+/// System selector used by a [`Poll`] instance, see [`Poll::with_backend`].
+#[cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "os-poll", any(target_os = "android", target_os = "linux"))))
+)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// [epoll], the default used by [`Poll::new`].
+    ///
+    /// [epoll]: http://man7.org/linux/man-pages/man7/epoll.7.html
+    Epoll,
+    /// [`poll(2)`], for environments where epoll isn't available, e.g. due to
+    /// seccomp filters.
+    ///
+    /// `poll(2)` is level-triggered, Mio emulates edge-triggered events by no
+    /// longer polling for an interest once an event is returned for it,
+    /// until an I/O operation returns a [`WouldBlock`] error or the event
+    /// source is reregistered. This works for all I/O types provided by Mio,
+    /// but event sources that register a file descriptor directly, e.g.
+    /// using `SourceFd`, must be reregistered to receive more events.
+    /// Because all interests are re-armed after a `WouldBlock` error this
+    /// backend returns more spurious events than epoll.
+    ///
+    /// The time it takes to poll grows linearly with the number of
+    /// registered event sources, and busy polling (see
+    /// [`Poll::set_busy_poll`]) only spins in user space.
+    ///
+    /// [`poll(2)`]: http://man7.org/linux/man-pages/man2/poll.2.html
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    Poll,
 }
 
 /// Registers I/O resources.
 pub struct Registry {
-    selector: sys::Selector,
+    /// Shared with the `Registry`s returned by `shared`.
+    selector: Arc<sys::Selector>,
 }
 
 impl Poll {
@@ -311,6 +358,53 @@ impl Poll {
     ///
     /// [struct]: #
     pub fn poll(&mut self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        match self.busy_poll {
+            Some(window) if timeout != Some(Duration::from_millis(0)) => {
+                self.busy_poll_then_block(events, timeout, window)?
+            }
+            _ => self.registry.selector.select(events.sys(), timeout)?,
+        }
+
+        #[cfg(feature = "os-poll")]
+        {
+            if let Some(recorder) = self.recorder.as_mut() {
+                if let Err(err) = recorder.record(events) {
+                    self.recorder = None;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Spins with zero timeout polls for at most `window` (or `timeout` if
+    /// that is shorter) and then blocks for the remainder of `timeout`.
+    fn busy_poll_then_block(
+        &mut self,
+        events: &mut Events,
+        timeout: Option<Duration>,
+        window: Duration,
+    ) -> io::Result<()> {
+        let start = Instant::now();
+        let spin = timeout.map_or(window, |timeout| cmp::min(timeout, window));
+        loop {
+            self.registry
+                .selector
+                .select(events.sys(), Some(Duration::from_millis(0)))?;
+            if !events.is_empty() {
+                return Ok(());
+            }
+
+            if start.elapsed() >= spin {
+                break;
+            }
+        }
+
+        let timeout = timeout.map(|timeout| {
+            timeout
+                .checked_sub(start.elapsed())
+                .unwrap_or_else(|| Duration::from_millis(0))
+        });
         self.registry.selector.select(events.sys(), timeout)
     }
 }
@@ -352,9 +446,237 @@ cfg_os_poll! {
         /// ```
         pub fn new() -> io::Result<Poll> {
             sys::Selector::new().map(|selector| Poll {
-                registry: Registry { selector },
+                registry: Registry {
+                    selector: Arc::new(selector),
+                },
+                busy_poll: None,
+                recorder: None,
+            })
+        }
+
+        /// Return a new `Poll` handle using the system selector `backend`.
+        ///
+        /// [`Poll::new`] uses [`Backend::Epoll`].
+        ///
+        /// # Notes
+        ///
+        /// With [`Backend::Poll`] the file descriptor returned by `as_raw_fd`
+        /// can't be used to wait for events, e.g. by registering it with
+        /// another `Poll` instance.
+        ///
+        /// # Examples
+        ///
+        #[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+        #[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+        /// # use std::error::Error;
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// use mio::{Backend, Events, Interest, Poll, Token};
+        /// use mio::net::TcpListener;
+        /// use std::time::Duration;
+        ///
+        /// let mut poll = Poll::with_backend(Backend::Poll)?;
+        /// let mut events = Events::with_capacity(1024);
+        ///
+        /// let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+        /// poll.registry().register(&mut listener, Token(0), Interest::READABLE)?;
+        ///
+        /// poll.poll(&mut events, Some(Duration::from_millis(10)))?;
+        /// assert!(events.is_empty());
+        /// #     Ok(())
+        /// # }
+        /// ```
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        #[cfg_attr(docsrs, doc(cfg(any(target_os = "android", target_os = "linux"))))]
+        pub fn with_backend(backend: Backend) -> io::Result<Poll> {
+            let selector = match backend {
+                Backend::Epoll => sys::Selector::new(),
+                Backend::Poll => sys::Selector::new_poll(),
+            };
+            selector.map(|selector| Poll {
+                registry: Registry {
+                    selector: Arc::new(selector),
+                },
+                busy_poll: None,
+                recorder: None,
+            })
+        }
+
+        /// Enable or disable busy polling.
+        ///
+        /// With busy polling enabled [`Poll::poll`] first spins, repeatedly
+        /// polling the system selector with a zero timeout, for at most
+        /// `window` before falling back to a blocking poll for the remainder
+        /// of the timeout. This trades CPU time for lower latency between an
+        /// event source becoming ready and the event being returned. Passing
+        /// `None` disables busy polling, which is the default.
+        ///
+        /// On Linux this also sets the busy poll parameters of the epoll
+        /// instance (`EPIOCSPARAMS`), making the kernel busy poll the network
+        /// device queues of the registered sockets while in `epoll_wait`. If
+        /// the kernel doesn't support this (added in Linux 6.9) only the user
+        /// space spinning is done. To let the kernel busy poll the queues of an
+        /// individual socket see [`TcpSocket::set_busy_poll`] and
+        /// [`UdpSocket::set_busy_poll`].
+        ///
+        /// [`TcpSocket::set_busy_poll`]: crate::net::TcpSocket::set_busy_poll
+        /// [`UdpSocket::set_busy_poll`]: crate::net::UdpSocket::set_busy_poll
+        ///
+        /// # Notes
+        ///
+        /// A zero timeout passed to `poll` is never extended by the busy poll
+        /// window.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// # use std::error::Error;
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// use mio::{Events, Poll};
+        /// use std::time::Duration;
+        ///
+        /// let mut poll = Poll::new()?;
+        /// let mut events = Events::with_capacity(1024);
+        ///
+        /// // Spin for at most 50 microseconds before blocking.
+        /// poll.set_busy_poll(Some(Duration::from_micros(50)))?;
+        ///
+        /// poll.poll(&mut events, Some(Duration::from_millis(10)))?;
+        /// assert!(events.is_empty());
+        /// #     Ok(())
+        /// # }
+        /// ```
+        pub fn set_busy_poll(&mut self, window: Option<Duration>) -> io::Result<()> {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            self.registry
+                .selector
+                .set_busy_poll(window.unwrap_or_else(|| Duration::from_millis(0)))?;
+            self.busy_poll = window;
+            Ok(())
+        }
+
+        /// Returns the busy poll window set by [`Poll::set_busy_poll`], if any.
+        pub fn busy_poll(&self) -> Option<Duration> {
+            self.busy_poll
+        }
+
+        /// Start recording the events returned by [`Poll::poll`] to `writer`.
+        ///
+        /// Every batch of events returned by `poll` is written to `writer`,
+        /// including the tokens, readiness and the time since the recording
+        /// started, see [`Replay`] for the format. The recording can be
+        /// replayed using [`Replay`], e.g. to run event handling logic again
+        /// against events captured in production.
+        ///
+        /// `writer` is flushed after every batch. If writing to `writer` fails
+        /// the error is returned by `poll` (after `events` is filled) and the
+        /// recording is stopped. Recording replaces any previous recording.
+        ///
+        /// [`Replay`]: crate::event::Replay
+        ///
+        /// # Examples
+        ///
+        /// See [`Replay`].
+        pub fn record<W>(&mut self, writer: W) -> io::Result<()>
+        where
+            W: Write + Send + 'static,
+        {
+            self.recorder = Some(Recorder::new(Box::new(writer))?);
+            Ok(())
+        }
+
+        /// Stop recording events, returning the writer passed to
+        /// [`Poll::record`], if any.
+        pub fn stop_recording(&mut self) -> Option<Box<dyn Write + Send>> {
+            self.recorder.take().map(Recorder::into_inner)
+        }
+
+        /// Return a new `Poll` handle that supports
+        /// [`Poll::reinit_after_fork`].
+        ///
+        /// To be able to register all event sources again after a `fork(2)`
+        /// the `Poll` instance keeps track of the registered event sources
+        /// (and their tokens and interests). This requires taking a lock on
+        /// every registration change, which `Poll::new` avoids.
+        ///
+        /// Event sources provided by Mio, e.g. [`TcpListener`], stop being
+        /// tracked when they're deregistered or dropped. File descriptors
+        /// registered using `SourceFd` must be deregistered before they're
+        /// closed, otherwise `reinit_after_fork` may register another file
+        /// that reused the file descriptor number.
+        ///
+        /// [`TcpListener`]: crate::net::TcpListener
+        #[cfg(unix)]
+        #[cfg_attr(docsrs, doc(cfg(unix)))]
+        pub fn with_fork_support() -> io::Result<Poll> {
+            sys::Selector::with_fork_support().map(|selector| Poll {
+                registry: Registry {
+                    selector: Arc::new(selector),
+                },
+                busy_poll: None,
+                recorder: None,
             })
         }
+
+        /// Re-initialise the `Poll` instance in a child process after
+        /// `fork(2)`.
+        ///
+        /// A child process inherits the system selector (e.g. epoll) of the
+        /// parent, which means the parent and child share the registered event
+        /// sources, and events, in surprising ways. Or, in the case of kqueue,
+        /// the child doesn't inherit the selector at all. This creates a new
+        /// system selector for this `Poll` instance and registers all event
+        /// sources that are registered with it again, using the same tokens
+        /// and interests.
+        ///
+        /// The `Poll` instance must be created using
+        /// [`Poll::with_fork_support`], or on Linux using
+        /// `Poll::with_backend(Backend::Poll)` (which keeps all registrations
+        /// in user space), otherwise an error is returned.
+        ///
+        /// The file descriptor of the `Poll` instance, as returned by
+        /// `as_raw_fd`, doesn't change.
+        ///
+        /// # Notes
+        ///
+        /// `Registry`s created using [`Registry::try_clone`] before calling this
+        /// keep referring to the old selector, they should be cloned again.
+        ///
+        /// [`Waker`]s keep working, but on some platforms they are shared with
+        /// the parent process, meaning that waking either process may wake
+        /// both.
+        ///
+        /// [`Waker`]: crate::Waker
+        ///
+        /// # Examples
+        ///
+        #[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+        #[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+        /// # use std::error::Error;
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// use mio::net::TcpListener;
+        /// use mio::{Interest, Poll, Token};
+        ///
+        /// let mut poll = Poll::with_fork_support()?;
+        /// let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+        /// poll.registry().register(&mut listener, Token(0), Interest::READABLE)?;
+        ///
+        /// // Fork worker processes here, then in each child process:
+        /// poll.reinit_after_fork()?;
+        /// #     Ok(())
+        /// # }
+        /// ```
+        #[cfg(unix)]
+        #[cfg_attr(docsrs, doc(cfg(unix)))]
+        pub fn reinit_after_fork(&mut self) -> io::Result<()> {
+            self.registry.selector.reinit()?;
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            {
+                if let Some(window) = self.busy_poll {
+                    self.registry.selector.set_busy_poll(window)?;
+                }
+            }
+            Ok(())
+        }
     }
 }
 
@@ -610,14 +932,171 @@ impl Registry {
         source.deregister(self)
     }
 
+    /// Register multiple [`event::Source`]s with the `Poll` instance, all or
+    /// nothing.
+    ///
+    /// Each entry is registered as if by calling [`register`] with the
+    /// entry's `token` and `interests`, in order. If registering any of the
+    /// sources fails all sources of the preceding entries, which were
+    /// registered successfully, are deregistered again (in reverse order) and
+    /// the error of the failed registration is returned. Errors returned while
+    /// rolling back are logged and otherwise ignored.
+    ///
+    /// See [`register`] for the requirements on the sources.
+    ///
+    /// [`event::Source`]: ./event/trait.Source.html
+    /// [`register`]: struct.Registry.html#method.register
+    ///
+    /// # Notes
+    ///
+    /// On platforms using kqueue the registrations of all sources are
+    /// submitted using a single `kevent(2)` call (with a change list), after
+    /// all sources have been registered. On other platforms the sources are
+    /// registered using one system call per source (or more, depending on the
+    /// source).
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+    #[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+    /// # use std::error::Error;
+    /// # use std::net;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// use mio::{Poll, Interest, Token};
+    /// use mio::net::{TcpStream, UdpSocket};
+    ///
+    /// let poll = Poll::new()?;
+    ///
+    /// let listener = net::TcpListener::bind("127.0.0.1:0")?;
+    /// let mut stream = TcpStream::connect(listener.local_addr()?)?;
+    /// let mut socket = UdpSocket::bind("127.0.0.1:0".parse()?)?;
+    ///
+    /// // Either both are registered, or neither is.
+    /// poll.registry().register_batch(&mut [
+    ///     (&mut stream, Token(0), Interest::READABLE | Interest::WRITABLE),
+    ///     (&mut socket, Token(1), Interest::READABLE),
+    /// ])?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn register_batch(
+        &self,
+        entries: &mut [(&mut dyn event::Source, Token, Interest)],
+    ) -> io::Result<()> {
+        // Collects the changes of the sources registered below. Dropping it
+        // (e.g. on error) discards changes that weren't submitted yet.
+        #[cfg(all(
+            feature = "os-poll",
+            any(
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "ios",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            )
+        ))]
+        let batch = self.selector.start_batch();
+
+        for n in 0..entries.len() {
+            let (source, token, interests) = &mut entries[n];
+            if let Err(err) = self.register(&mut **source, *token, *interests) {
+                self.roll_back_batch(&mut entries[..n]);
+                return Err(err);
+            }
+        }
+
+        #[cfg(all(
+            feature = "os-poll",
+            any(
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "ios",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            )
+        ))]
+        {
+            if let Err(err) = batch.submit() {
+                self.roll_back_batch(entries);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deregister the sources of `entries` (in reverse order) after
+    /// [`Registry::register_batch`] failed.
+    fn roll_back_batch(&self, entries: &mut [(&mut dyn event::Source, Token, Interest)]) {
+        for (source, token, _) in entries.iter_mut().rev() {
+            if let Err(err) = self.deregister(&mut **source) {
+                warn!(
+                    "error deregistering event source while rolling back batch registration: token={:?}, err={}",
+                    token, err
+                );
+            }
+        }
+    }
+
+    /// Determine the current readiness of `source` for `interests`, without
+    /// waiting for an event.
+    ///
+    /// With edge-triggered events there is no new event for a source that is
+    /// still ready after it's partially drained, e.g. after only reading some
+    /// of the available data. This can be used to check whether such a source
+    /// is still ready, using a `poll(2)` call with a zero timeout on the file
+    /// descriptor of `source`.
+    ///
+    /// `source` doesn't need to be registered and probing it doesn't change
+    /// its registration, or the events returned by [`Poll::poll`].
+    ///
+    /// [`Poll::poll`]: crate::Poll::poll
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+    #[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+    /// # use std::error::Error;
+    /// # use std::net;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// use mio::{Poll, Interest};
+    /// use mio::net::TcpStream;
+    /// use std::io::Write;
+    ///
+    /// let poll = Poll::new()?;
+    ///
+    /// let listener = net::TcpListener::bind("127.0.0.1:0")?;
+    /// let stream = TcpStream::connect(listener.local_addr()?)?;
+    /// let (mut peer, _) = listener.accept()?;
+    ///
+    /// let readiness = poll.registry().probe(&stream, Interest::READABLE)?;
+    /// assert!(!readiness.is_readable());
+    ///
+    /// peer.write_all(b"hello")?;
+    /// # std::thread::sleep(std::time::Duration::from_millis(100));
+    /// let readiness = poll.registry().probe(&stream, Interest::READABLE)?;
+    /// assert!(readiness.is_readable());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(unix, feature = "os-poll"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "os-poll"))))]
+    pub fn probe<S>(&self, source: &S, interests: Interest) -> io::Result<event::Readiness>
+    where
+        S: AsRawFd + ?Sized,
+    {
+        sys::probe(source.as_raw_fd(), interests)
+    }
+
     /// Creates a new independently owned `Registry`.
     ///
     /// Event sources registered with this `Registry` will be registered with
     /// the original `Registry` and `Poll` instance.
     pub fn try_clone(&self) -> io::Result<Registry> {
-        self.selector
-            .try_clone()
-            .map(|selector| Registry { selector })
+        self.selector.try_clone().map(|selector| Registry {
+            selector: Arc::new(selector),
+        })
     }
 
     /// Internal check to ensure only a single `Waker` is active per [`Poll`]
@@ -629,6 +1108,16 @@ impl Registry {
         }
     }
 
+    /// Returns a `Registry` sharing the `sys::Selector` with this one.
+    ///
+    /// Unlike [`Registry::try_clone`] this doesn't create a new selector
+    /// handle, e.g. a duplicate file descriptor on Unix.
+    pub(crate) fn shared(&self) -> Registry {
+        Registry {
+            selector: Arc::clone(&self.selector),
+        }
+    }
+
     /// Get access to the `sys::Selector`.
     pub(crate) fn selector(&self) -> &sys::Selector {
         &self.selector
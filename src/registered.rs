@@ -0,0 +1,164 @@
+use crate::{event, Interest, Registry, Token};
+
+use std::ops::{Deref, DerefMut};
+use std::{fmt, io};
+
+use log::warn;
+
+/// An [`event::Source`] registered with a [`Registry`], deregistering itself
+/// when dropped.
+///
+/// Created by [`Registry::register_owned`]. `Registered` dereferences to the
+/// source, so it can be used in place of the source for I/O.
+///
+/// # Notes
+///
+/// `Registered` shares the selector of the [`Poll`] instance, keeping it
+/// alive even if the `Poll` instance is dropped first.
+///
+/// Errors returned when deregistering the source on drop are logged and
+/// otherwise ignored, use [`into_inner`] to handle them.
+///
+/// [`Poll`]: crate::Poll
+/// [`into_inner`]: Registered::into_inner
+///
+/// # Examples
+///
+#[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+#[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::TcpListener;
+/// use mio::{Interest, Poll, Token};
+///
+/// let poll = Poll::new()?;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+/// let mut listener = poll
+///     .registry()
+///     .register_owned(listener, Token(0), Interest::READABLE)?;
+///
+/// // The source can be used directly.
+/// let _addr = listener.local_addr()?;
+///
+/// // And reregistered without access to the `Registry`.
+/// listener.reregister(Interest::READABLE | Interest::WRITABLE)?;
+///
+/// // Dropping `listener` deregisters it.
+/// drop(listener);
+/// #     Ok(())
+/// # }
+/// ```
+pub struct Registered<S: event::Source> {
+    registry: Registry,
+    token: Token,
+    interests: Interest,
+    /// Always `Some`, only `None` after `into_inner` took the source.
+    source: Option<S>,
+}
+
+impl Registry {
+    /// Register `source` with the `Poll` instance, returning a guard that
+    /// deregisters the source when dropped.
+    ///
+    /// See [`register`] for the meaning of the arguments and [`Registered`]
+    /// for more information.
+    ///
+    /// [`register`]: Registry::register
+    ///
+    /// # Notes
+    ///
+    /// If registering fails `source` is dropped.
+    pub fn register_owned<S>(
+        &self,
+        mut source: S,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<Registered<S>>
+    where
+        S: event::Source,
+    {
+        let registry = self.shared();
+        registry.register(&mut source, token, interests)?;
+        Ok(Registered {
+            registry,
+            token,
+            interests,
+            source: Some(source),
+        })
+    }
+}
+
+impl<S: event::Source> Registered<S> {
+    /// Returns the token the source is registered with.
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// Returns the interests the source is registered with.
+    pub fn interests(&self) -> Interest {
+        self.interests
+    }
+
+    /// Reregister the source with new `interests`, keeping the same token.
+    ///
+    /// See [`Registry::reregister`].
+    pub fn reregister(&mut self, interests: Interest) -> io::Result<()> {
+        let source = self.source.as_mut().unwrap();
+        self.registry.reregister(source, self.token, interests)?;
+        self.interests = interests;
+        Ok(())
+    }
+
+    /// Deregister the source and return it.
+    ///
+    /// If deregistering fails the error is returned together with the source,
+    /// which may still be registered.
+    pub fn into_inner(mut self) -> Result<S, (S, io::Error)> {
+        let mut source = self.source.take().unwrap();
+        match self.registry.deregister(&mut source) {
+            Ok(()) => Ok(source),
+            Err(err) => Err((source, err)),
+        }
+    }
+}
+
+impl<S: event::Source> Deref for Registered<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.source.as_ref().unwrap()
+    }
+}
+
+impl<S: event::Source> DerefMut for Registered<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        self.source.as_mut().unwrap()
+    }
+}
+
+impl<S: event::Source> Drop for Registered<S> {
+    fn drop(&mut self) {
+        if let Some(source) = self.source.as_mut() {
+            if let Err(err) = self.registry.deregister(source) {
+                warn!(
+                    "error deregistering event source on drop: token={:?}, err={}",
+                    self.token, err
+                );
+            }
+        }
+    }
+}
+
+impl<S> fmt::Debug for Registered<S>
+where
+    S: event::Source + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registered")
+            .field("token", &self.token)
+            .field("interests", &self.interests)
+            .field("source", &self.source)
+            .finish()
+    }
+}
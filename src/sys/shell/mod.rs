@@ -19,10 +19,11 @@ cfg_net! {
 
 cfg_io_source! {
     use std::io;
+    #[cfg(unix)]
+    use std::os::unix::io::RawFd;
     #[cfg(windows)]
     use std::os::windows::io::RawSocket;
 
-    #[cfg(windows)]
     use crate::{Registry, Token, Interest};
 
     pub(crate) struct IoSourceState;
@@ -42,6 +43,33 @@ cfg_io_source! {
         }
     }
 
+    #[cfg(unix)]
+    impl IoSourceState {
+        pub fn register(
+            &mut self,
+            registry: &Registry,
+            token: Token,
+            interests: Interest,
+            fd: RawFd,
+        ) -> io::Result<()> {
+            registry.selector().register(fd, token, interests)
+        }
+
+        pub fn reregister(
+            &mut self,
+            registry: &Registry,
+            token: Token,
+            interests: Interest,
+            fd: RawFd,
+        ) -> io::Result<()> {
+            registry.selector().reregister(fd, token, interests)
+        }
+
+        pub fn deregister(&mut self, registry: &Registry, fd: RawFd) -> io::Result<()> {
+            registry.selector().deregister(fd)
+        }
+    }
+
     #[cfg(windows)]
     impl IoSourceState {
          pub fn register(
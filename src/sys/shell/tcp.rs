@@ -13,6 +13,16 @@ pub(crate) fn new_v6_socket() -> io::Result<TcpSocket> {
     os_required!();
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn new_mptcp_v4_socket() -> io::Result<TcpSocket> {
+    os_required!();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn new_mptcp_v6_socket() -> io::Result<TcpSocket> {
+    os_required!();
+}
+
 pub(crate) fn bind(_socket: TcpSocket, _addr: SocketAddr) -> io::Result<()> {
     os_required!();
 }
@@ -37,6 +47,14 @@ pub(crate) fn get_reuseaddr(_: TcpSocket) -> io::Result<bool> {
     os_required!();
 }
 
+pub(crate) fn set_only_v6(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!();
+}
+
+pub(crate) fn get_only_v6(_: TcpSocket) -> io::Result<bool> {
+    os_required!();
+}
+
 #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
 pub(crate) fn set_reuseport(_: TcpSocket, _: bool) -> io::Result<()> {
     os_required!();
@@ -118,6 +136,127 @@ pub(crate) fn get_keepalive_retries(_: TcpSocket) -> io::Result<Option<u32>> {
     os_required!()
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_busy_poll(_: TcpSocket, _: Duration) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_busy_poll(_: TcpSocket) -> io::Result<Duration> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_prefer_busy_poll(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_prefer_busy_poll(_: TcpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_fastopen(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_fastopen(_: TcpSocket) -> io::Result<u32> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn connect_fastopen(_: TcpSocket, _: SocketAddr) -> io::Result<net::TcpStream> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_user_timeout(_: TcpSocket, _: Duration) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_user_timeout(_: TcpSocket) -> io::Result<Duration> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_notsent_lowat(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_notsent_lowat(_: TcpSocket) -> io::Result<u32> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_device(_: TcpSocket, _: &[u8]) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_device(_: TcpSocket) -> io::Result<Option<Vec<u8>>> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_mark(_: TcpSocket, _: u32) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_mark(_: TcpSocket) -> io::Result<u32> {
+    os_required!()
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) fn get_tcp_info(_: TcpSocket) -> io::Result<crate::net::TcpInfo> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_congestion_control(_: TcpSocket, _: &str) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_congestion_control(_: TcpSocket) -> io::Result<String> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_is_mptcp(_: TcpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_address_no_port(_: TcpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_address_no_port(_: TcpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_local_port_range(_: TcpSocket, _: u16, _: u16) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_local_port_range(_: TcpSocket) -> io::Result<(u16, u16)> {
+    os_required!()
+}
+
 pub fn accept(_: &net::TcpListener) -> io::Result<(net::TcpStream, SocketAddr)> {
     os_required!();
 }
@@ -1,5 +1,16 @@
+// These socket options are shared with TCP sockets.
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+pub(crate) use super::tcp::{get_reuseport, set_reuseport};
+pub(crate) use super::tcp::{
+    get_recv_buffer_size, get_reuseaddr, get_send_buffer_size, set_recv_buffer_size,
+    set_reuseaddr, set_send_buffer_size,
+};
 use std::io;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::net::IpAddr;
 use std::net::{self, SocketAddr};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::time::Duration;
 
 pub fn bind(_: SocketAddr) -> io::Result<net::UdpSocket> {
     os_required!()
@@ -8,3 +19,152 @@ pub fn bind(_: SocketAddr) -> io::Result<net::UdpSocket> {
 pub(crate) fn only_v6(_: &net::UdpSocket) -> io::Result<bool> {
     os_required!()
 }
+
+pub(crate) type UdpSocket = i32;
+
+pub(crate) fn new_v4_socket() -> io::Result<UdpSocket> {
+    os_required!()
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<UdpSocket> {
+    os_required!()
+}
+
+pub(crate) fn bind_socket(_: UdpSocket, _: SocketAddr) -> io::Result<net::UdpSocket> {
+    os_required!()
+}
+
+pub(crate) fn connect_socket(_: UdpSocket, _: SocketAddr) -> io::Result<net::UdpSocket> {
+    os_required!()
+}
+
+pub(crate) fn close(_: UdpSocket) {
+    os_required!()
+}
+
+pub(crate) fn set_broadcast(_: UdpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+pub(crate) fn get_broadcast(_: UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+pub(crate) fn set_only_v6(_: UdpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+pub(crate) fn get_only_v6(_: UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_busy_poll(_: &net::UdpSocket, _: Duration) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn busy_poll(_: &net::UdpSocket) -> io::Result<Duration> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_prefer_busy_poll(_: &net::UdpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn prefer_busy_poll(_: &net::UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn bind_device(_: &net::UdpSocket, _: &[u8]) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn device(_: &net::UdpSocket) -> io::Result<Option<Vec<u8>>> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_mark(_: &net::UdpSocket, _: u32) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn mark(_: &net::UdpSocket) -> io::Result<u32> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn recv_many<B: AsMut<[u8]>>(
+    _: &net::UdpSocket,
+    _: &mut [B],
+    _: &mut [crate::net::RecvMeta],
+) -> io::Result<usize> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_many<B: AsRef<[u8]>>(
+    _: &net::UdpSocket,
+    _: &[B],
+    _: &[SocketAddr],
+) -> io::Result<usize> {
+    os_required!()
+}
+
+pub(crate) fn recv_msg(
+    _: &net::UdpSocket,
+    _: &mut [u8],
+    _: &mut crate::net::RecvMeta,
+) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_segmented(
+    _: &net::UdpSocket,
+    _: &[u8],
+    _: u16,
+    _: SocketAddr,
+) -> io::Result<usize> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn supports_gso(_: &net::UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_gro(_: &net::UdpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn gro(_: &net::UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_msg(
+    _: &net::UdpSocket,
+    _: &[u8],
+    _: SocketAddr,
+    _: IpAddr,
+) -> io::Result<usize> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_recv_pktinfo(_: &net::UdpSocket, _: bool) -> io::Result<()> {
+    os_required!()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn recv_pktinfo(_: &net::UdpSocket) -> io::Result<bool> {
+    os_required!()
+}
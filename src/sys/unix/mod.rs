@@ -15,7 +15,7 @@ macro_rules! syscall {
 
 cfg_os_poll! {
     mod selector;
-    pub(crate) use self::selector::{event, Event, Events, Selector};
+    pub(crate) use self::selector::{event, Event, Events, RegistrationGuard, Selector};
 
     mod sourcefd;
     pub use self::sourcefd::SourceFd;
@@ -23,6 +23,9 @@ cfg_os_poll! {
     mod waker;
     pub(crate) use self::waker::Waker;
 
+    mod probe;
+    pub(crate) use self::probe::probe;
+
     cfg_net! {
         mod net;
 
@@ -34,22 +37,78 @@ cfg_os_poll! {
 
     cfg_io_source! {
         use std::io;
+        use std::os::unix::io::RawFd;
+
+        use crate::{Interest, Registry, Token};
 
-        // Both `kqueue` and `epoll` don't need to hold any user space state.
-        pub(crate) struct IoSourceState;
+        // Both `kqueue` and `epoll` don't need to hold any user space state,
+        // only the `poll(2)` selector needs to re-arm registrations. Selectors
+        // created with fork support track the registration until the source is
+        // dropped.
+        pub(crate) struct IoSourceState {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            rearm: Option<selector::Rearm>,
+            registration: Option<RegistrationGuard>,
+        }
 
         impl IoSourceState {
             pub fn new() -> IoSourceState {
-                IoSourceState
+                IoSourceState {
+                    #[cfg(any(target_os = "android", target_os = "linux"))]
+                    rearm: None,
+                    registration: None,
+                }
             }
 
             pub fn do_io<T, F, R>(&self, f: F, io: &T) -> io::Result<R>
             where
                 F: FnOnce(&T) -> io::Result<R>,
             {
-                // We don't hold state, so we can just call the function and
-                // return.
-                f(io)
+                let result = f(io);
+                #[cfg(any(target_os = "android", target_os = "linux"))]
+                {
+                    if let (Some(rearm), Err(err)) = (&self.rearm, &result) {
+                        if err.kind() == io::ErrorKind::WouldBlock {
+                            rearm.rearm();
+                        }
+                    }
+                }
+                result
+            }
+
+            pub fn register(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                registry.selector().register(fd, token, interests)?;
+                #[cfg(any(target_os = "android", target_os = "linux"))]
+                {
+                    self.rearm = registry.selector().rearm_handle(fd);
+                }
+                self.registration = registry.selector().registration_guard(fd);
+                Ok(())
+            }
+
+            pub fn reregister(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+                fd: RawFd,
+            ) -> io::Result<()> {
+                registry.selector().reregister(fd, token, interests)
+            }
+
+            pub fn deregister(&mut self, registry: &Registry, fd: RawFd) -> io::Result<()> {
+                #[cfg(any(target_os = "android", target_os = "linux"))]
+                {
+                    self.rearm = None;
+                }
+                self.registration = None;
+                registry.selector().deregister(fd)
             }
         }
     }
@@ -1,6 +1,8 @@
 use std::io;
 use std::mem::size_of;
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::time::Duration;
 
 pub(crate) fn new_ip_socket(addr: SocketAddr, socket_type: libc::c_int) -> io::Result<libc::c_int> {
     let domain = match addr {
@@ -13,6 +15,15 @@ pub(crate) fn new_ip_socket(addr: SocketAddr, socket_type: libc::c_int) -> io::R
 
 /// Create a new non-blocking socket.
 pub(crate) fn new_socket(domain: libc::c_int, socket_type: libc::c_int) -> io::Result<libc::c_int> {
+    new_socket_with_protocol(domain, socket_type, 0)
+}
+
+/// Create a new non-blocking socket using `protocol`.
+pub(crate) fn new_socket_with_protocol(
+    domain: libc::c_int,
+    socket_type: libc::c_int,
+    protocol: libc::c_int,
+) -> io::Result<libc::c_int> {
     #[cfg(any(
         target_os = "android",
         target_os = "dragonfly",
@@ -26,7 +37,7 @@ pub(crate) fn new_socket(domain: libc::c_int, socket_type: libc::c_int) -> io::R
 
     // Gives a warning for platforms without SOCK_NONBLOCK.
     #[allow(clippy::let_and_return)]
-    let socket = syscall!(socket(domain, socket_type, 0));
+    let socket = syscall!(socket(domain, socket_type, protocol));
 
     // Mimick `libstd` and set `SO_NOSIGPIPE` on apple systems.
     #[cfg(target_vendor = "apple")]
@@ -60,6 +71,167 @@ pub(crate) fn new_socket(domain: libc::c_int, socket_type: libc::c_int) -> io::R
     socket
 }
 
+pub(crate) fn set_only_v6(socket: libc::c_int, only_v6: bool) -> io::Result<()> {
+    let val: libc::c_int = if only_v6 { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_IPV6,
+        libc::IPV6_V6ONLY,
+        &val as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn get_only_v6(socket: libc::c_int) -> io::Result<bool> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = size_of::<libc::c_int>() as libc::socklen_t;
+
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_IPV6,
+        libc::IPV6_V6ONLY,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+/// `SO_PREFER_BUSY_POLL` isn't defined in all `libc` versions we support.
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    not(target_arch = "sparc64")
+))]
+const SO_PREFER_BUSY_POLL: libc::c_int = 69;
+#[cfg(all(
+    any(target_os = "android", target_os = "linux"),
+    target_arch = "sparc64"
+))]
+const SO_PREFER_BUSY_POLL: libc::c_int = 0x0048;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_busy_poll(socket: libc::c_int, dur: Duration) -> io::Result<()> {
+    let usecs = std::cmp::min(dur.as_micros(), libc::c_int::MAX as u128) as libc::c_int;
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BUSY_POLL,
+        &usecs as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_busy_poll(socket: libc::c_int) -> io::Result<Duration> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = size_of::<libc::c_int>() as libc::socklen_t;
+
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BUSY_POLL,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(Duration::from_micros(optval as u64))
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_prefer_busy_poll(socket: libc::c_int, prefer: bool) -> io::Result<()> {
+    let val: libc::c_int = if prefer { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        SO_PREFER_BUSY_POLL,
+        &val as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_prefer_busy_poll(socket: libc::c_int) -> io::Result<bool> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = size_of::<libc::c_int>() as libc::socklen_t;
+
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        SO_PREFER_BUSY_POLL,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_device(socket: libc::c_int, interface: &[u8]) -> io::Result<()> {
+    // An empty name removes the binding.
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BINDTODEVICE,
+        interface.as_ptr() as *const libc::c_void,
+        interface.len() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_device(socket: libc::c_int) -> io::Result<Option<Vec<u8>>> {
+    let mut buf = [0u8; libc::IFNAMSIZ];
+    let mut optlen = buf.len() as libc::socklen_t;
+
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BINDTODEVICE,
+        buf.as_mut_ptr() as *mut _,
+        &mut optlen,
+    ))?;
+
+    // The returned name includes the terminating null byte, if any.
+    let name = &buf[..optlen as usize];
+    let name = name.split(|b| *b == 0).next().unwrap_or(name);
+    if name.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(name.to_vec()))
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_mark(socket: libc::c_int, mark: u32) -> io::Result<()> {
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_MARK,
+        &mark as *const u32 as *const libc::c_void,
+        size_of::<u32>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_mark(socket: libc::c_int) -> io::Result<u32> {
+    let mut optval: u32 = 0;
+    let mut optlen = size_of::<u32>() as libc::socklen_t;
+
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_MARK,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval)
+}
+
 /// A type with the same memory layout as `libc::sockaddr`. Used in converting Rust level
 /// SocketAddr* types into their system representation. The benefit of this specific
 /// type over using `libc::sockaddr_storage` is that this type is exactly as large as it
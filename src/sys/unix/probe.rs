@@ -0,0 +1,70 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use crate::event::Readiness;
+use crate::Interest;
+
+/// Determine the current readiness of `fd` using a non-blocking `poll(2)`.
+pub(crate) fn probe(fd: RawFd, interests: Interest) -> io::Result<Readiness> {
+    let mut events = 0;
+    if interests.is_readable() {
+        events |= libc::POLLIN | libc::POLLPRI;
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            events |= libc::POLLRDHUP;
+        }
+    }
+    if interests.is_writable() {
+        events |= libc::POLLOUT;
+    }
+
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+    loop {
+        match syscall!(poll(&mut poll_fd, 1, 0)) {
+            Ok(_) => break,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    let revents = poll_fd.revents;
+    if revents & libc::POLLNVAL != 0 {
+        return Err(io::Error::from_raw_os_error(libc::EBADF));
+    }
+
+    let mut readiness = Readiness::default();
+    if revents & (libc::POLLIN | libc::POLLPRI) != 0 {
+        readiness.set_readable();
+    }
+    if revents & libc::POLLOUT != 0 {
+        readiness.set_writable();
+    }
+    if revents & libc::POLLERR != 0 {
+        readiness.set_error();
+    }
+    if revents & libc::POLLPRI != 0 {
+        readiness.set_priority();
+    }
+    // Same logic as `Event::is_read_closed` and `Event::is_write_closed` for
+    // epoll, see `sys::unix::selector::epoll`.
+    if revents & libc::POLLHUP != 0 {
+        readiness.set_read_closed();
+        readiness.set_write_closed();
+    }
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        if revents & libc::POLLIN != 0 && revents & libc::POLLRDHUP != 0 {
+            readiness.set_read_closed();
+        }
+    }
+    if (revents & libc::POLLOUT != 0 && revents & libc::POLLERR != 0)
+        || revents == libc::POLLERR
+    {
+        readiness.set_write_closed();
+    }
+    Ok(readiness)
+}
@@ -0,0 +1,130 @@
+//! Selector that dispatches to either the epoll or `poll(2)` based selector,
+//! selected at runtime.
+
+use super::{epoll, poll, RegistrationGuard};
+use crate::sys::Events;
+use crate::{Interest, Token};
+
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
+
+cfg_io_source! {
+    pub(crate) use self::poll::Rearm;
+}
+
+#[derive(Debug)]
+pub enum Selector {
+    Epoll(epoll::Selector),
+    Poll(poll::Selector),
+}
+
+/// Call `$method` on the selector, whichever backend it is.
+macro_rules! dispatch {
+    ($self: expr, $method: ident ( $($arg: expr),* )) => {
+        match $self {
+            Selector::Epoll(selector) => selector.$method($($arg),*),
+            Selector::Poll(selector) => selector.$method($($arg),*),
+        }
+    };
+}
+
+impl Selector {
+    pub fn new() -> io::Result<Selector> {
+        epoll::Selector::new().map(Selector::Epoll)
+    }
+
+    pub fn with_fork_support() -> io::Result<Selector> {
+        epoll::Selector::with_fork_support().map(Selector::Epoll)
+    }
+
+    pub fn new_poll() -> io::Result<Selector> {
+        poll::Selector::new().map(Selector::Poll)
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        match self {
+            Selector::Epoll(selector) => selector.try_clone().map(Selector::Epoll),
+            Selector::Poll(selector) => selector.try_clone().map(Selector::Poll),
+        }
+    }
+
+    pub fn reinit(&self) -> io::Result<()> {
+        dispatch!(self, reinit())
+    }
+
+    pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        dispatch!(self, select(events, timeout))
+    }
+
+    /// The `poll(2)` selector keeps all registrations in user space, so it
+    /// doesn't need a guard.
+    pub fn registration_guard(&self, fd: RawFd) -> Option<RegistrationGuard> {
+        match self {
+            Selector::Epoll(selector) => selector.registration_guard(fd),
+            Selector::Poll(_) => None,
+        }
+    }
+
+    // Used by `Waker`.
+    pub fn register_waker_fd(&self, fd: RawFd, token: Token) -> io::Result<()> {
+        match self {
+            Selector::Epoll(selector) => selector.register(fd, token, Interest::READABLE),
+            Selector::Poll(selector) => selector.register_waker_fd(fd, token),
+        }
+    }
+
+    pub fn set_busy_poll(&self, window: Duration) -> io::Result<()> {
+        match self {
+            Selector::Epoll(selector) => selector.set_busy_poll(window),
+            // Busy polling the network device queues requires epoll.
+            Selector::Poll(_) => Ok(()),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn register_waker(&self) -> bool {
+        dispatch!(self, register_waker())
+    }
+}
+
+// Without the `net` and `os-ext` features these are only used by `SourceFd`,
+// which is unused then.
+#[cfg_attr(not(any(feature = "net", feature = "os-ext")), allow(dead_code))]
+impl Selector {
+    pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        dispatch!(self, register(fd, token, interests))
+    }
+
+    pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        dispatch!(self, reregister(fd, token, interests))
+    }
+
+    pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        dispatch!(self, deregister(fd))
+    }
+}
+
+cfg_io_source! {
+    impl Selector {
+        /// Returns a handle to re-arm the registration of `fd` if the selector
+        /// emulates edge-triggered events.
+        pub fn rearm_handle(&self, fd: RawFd) -> Option<Rearm> {
+            match self {
+                Selector::Epoll(_) => None,
+                Selector::Poll(selector) => Some(selector.rearm_handle(fd)),
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        pub fn id(&self) -> usize {
+            dispatch!(self, id())
+        }
+    }
+}
+
+impl AsRawFd for Selector {
+    fn as_raw_fd(&self) -> RawFd {
+        dispatch!(self, as_raw_fd())
+    }
+}
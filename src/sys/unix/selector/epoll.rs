@@ -1,7 +1,8 @@
+use super::{RegistrationGuard, Registrations};
 use crate::{Interest, Token};
 
 use libc::{EPOLLET, EPOLLIN, EPOLLOUT, EPOLLRDHUP};
-use log::error;
+use log::{debug, error};
 use std::os::unix::io::{AsRawFd, RawFd};
 #[cfg(debug_assertions)]
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -10,31 +11,36 @@ use std::{cmp, i32, io, ptr};
 
 /// Unique id for use as `SelectorId`.
 #[cfg(debug_assertions)]
-static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+pub(super) static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
 
 #[derive(Debug)]
 pub struct Selector {
     #[cfg(debug_assertions)]
     id: usize,
     ep: RawFd,
+    /// Only tracked if created with fork support, see `reinit`.
+    registrations: Option<Registrations>,
     #[cfg(debug_assertions)]
     has_waker: AtomicBool,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
-        // According to libuv, `EPOLL_CLOEXEC` is not defined on Android API <
-        // 21. But `EPOLL_CLOEXEC` is an alias for `O_CLOEXEC` on that platform,
-        // so we use it instead.
-        #[cfg(target_os = "android")]
-        let flag = libc::O_CLOEXEC;
-        #[cfg(not(target_os = "android"))]
-        let flag = libc::EPOLL_CLOEXEC;
-
-        syscall!(epoll_create1(flag)).map(|ep| Selector {
+        Selector::with_registrations(None)
+    }
+
+    /// Create a new selector that keeps track of the registered file
+    /// descriptors, which is required by `reinit`.
+    pub fn with_fork_support() -> io::Result<Selector> {
+        Selector::with_registrations(Some(Registrations::default()))
+    }
+
+    fn with_registrations(registrations: Option<Registrations>) -> io::Result<Selector> {
+        new_epoll().map(|ep| Selector {
             #[cfg(debug_assertions)]
             id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             ep,
+            registrations,
             #[cfg(debug_assertions)]
             has_waker: AtomicBool::new(false),
         })
@@ -46,11 +52,47 @@ impl Selector {
             #[cfg(debug_assertions)]
             id: self.id,
             ep,
+            registrations: self.registrations.clone(),
             #[cfg(debug_assertions)]
             has_waker: AtomicBool::new(self.has_waker.load(Ordering::Acquire)),
         })
     }
 
+    /// Replaces the epoll instance with a new one, using the same file
+    /// descriptor, and registers all registered file descriptors with it.
+    ///
+    /// Returns an error if the selector wasn't created with fork support.
+    /// File descriptors that were closed without being deregistered are
+    /// skipped and forgotten.
+    pub fn reinit(&self) -> io::Result<()> {
+        let registrations = match self.registrations {
+            Some(ref registrations) => registrations,
+            None => return Err(super::no_fork_support()),
+        };
+        super::replace_fd(self.ep, new_epoll()?)?;
+
+        let mut registrations = registrations.lock().unwrap();
+        let mut closed = Vec::new();
+        for (&fd, &(token, interests)) in registrations.iter() {
+            let mut event = libc::epoll_event {
+                events: interests_to_epoll(interests),
+                u64: usize::from(token) as u64,
+            };
+            match syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_ADD, fd, &mut event)) {
+                Ok(_) => {}
+                Err(ref err) if super::is_closed_fd_error(err) => {
+                    debug!("not re-registering closed file descriptor: fd={}", fd);
+                    closed.push(fd);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        for fd in closed {
+            let _ = registrations.remove(&fd);
+        }
+        Ok(())
+    }
+
     pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
         // A bug in kernels < 2.6.37 makes timeouts larger than LONG_MAX / CONFIG_HZ
         // (approx. 30 minutes with CONFIG_HZ=1200) effectively infinite on 32 bits
@@ -84,7 +126,9 @@ impl Selector {
             u64: usize::from(token) as u64,
         };
 
-        syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_ADD, fd, &mut event)).map(|_| ())
+        syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_ADD, fd, &mut event))?;
+        super::track(&self.registrations, fd, Some((token, interests)));
+        Ok(())
     }
 
     pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
@@ -93,13 +137,76 @@ impl Selector {
             u64: usize::from(token) as u64,
         };
 
-        syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_MOD, fd, &mut event)).map(|_| ())
+        syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_MOD, fd, &mut event))?;
+        super::track(&self.registrations, fd, Some((token, interests)));
+        Ok(())
     }
 
     pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        super::track(&self.registrations, fd, None);
         syscall!(epoll_ctl(self.ep, libc::EPOLL_CTL_DEL, fd, ptr::null_mut())).map(|_| ())
     }
 
+    /// Returns a guard that stops tracking the registration of `fd` when
+    /// dropped, if the selector was created with fork support.
+    pub fn registration_guard(&self, fd: RawFd) -> Option<RegistrationGuard> {
+        super::registration_guard(&self.registrations, fd)
+    }
+
+    /// Sets the busy poll time of the epoll instance, a zero `window`
+    /// disables busy polling.
+    ///
+    /// This is a no-op on kernels that don't support `EPIOCSPARAMS`.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_busy_poll(&self, window: Duration) -> io::Result<()> {
+        // Not defined in all `libc` versions we support.
+        #[repr(C)]
+        struct EpollParams {
+            busy_poll_usecs: u32,
+            busy_poll_budget: u16,
+            prefer_busy_poll: u8,
+            pad: u8,
+        }
+        // `_IOW(0x8A, 0x01, struct epoll_params)`.
+        #[cfg(not(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "sparc64",
+        )))]
+        const EPIOCSPARAMS: libc::c_ulong = 0x4008_8A01;
+        #[cfg(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "sparc64",
+        ))]
+        const EPIOCSPARAMS: libc::c_ulong = 0x8008_8A01;
+
+        let params = EpollParams {
+            // The kernel doesn't accept values larger than `i32::MAX`.
+            busy_poll_usecs: cmp::min(window.as_micros(), i32::MAX as u128) as u32,
+            // Zero means the kernel's default budget.
+            busy_poll_budget: 0,
+            prefer_busy_poll: 0,
+            pad: 0,
+        };
+        match syscall!(ioctl(self.ep, EPIOCSPARAMS as _, &params as *const EpollParams)) {
+            Ok(_) => Ok(()),
+            // Kernel doesn't support the ioctl (< 6.9), or was build without
+            // `CONFIG_NET_RX_BUSY_POLL`.
+            Err(ref err)
+                if err.raw_os_error() == Some(libc::ENOTTY)
+                    || err.raw_os_error() == Some(libc::EOPNOTSUPP) =>
+            {
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     #[cfg(debug_assertions)]
     pub fn register_waker(&self) -> bool {
         self.has_waker.swap(true, Ordering::AcqRel)
@@ -129,6 +236,18 @@ impl Drop for Selector {
     }
 }
 
+fn new_epoll() -> io::Result<RawFd> {
+    // According to libuv, `EPOLL_CLOEXEC` is not defined on Android API <
+    // 21. But `EPOLL_CLOEXEC` is an alias for `O_CLOEXEC` on that platform,
+    // so we use it instead.
+    #[cfg(target_os = "android")]
+    let flag = libc::O_CLOEXEC;
+    #[cfg(not(target_os = "android"))]
+    let flag = libc::EPOLL_CLOEXEC;
+
+    syscall!(epoll_create1(flag))
+}
+
 fn interests_to_epoll(interests: Interest) -> u32 {
     let mut kind = EPOLLET;
 
@@ -149,9 +268,45 @@ pub type Events = Vec<Event>;
 pub mod event {
     use std::fmt;
 
-    use crate::sys::Event;
+    use crate::event::record;
+    use crate::sys::{Event, Events};
     use crate::Token;
 
+    /// Push an event with `token` and the recorded `readiness` to `events`.
+    pub fn push(events: &mut Events, token: Token, readiness: u8) {
+        let mut bits = 0;
+        if readiness & record::READABLE != 0 {
+            bits |= libc::EPOLLIN;
+        }
+        if readiness & record::WRITABLE != 0 {
+            bits |= libc::EPOLLOUT;
+        }
+        if readiness & record::ERROR != 0 {
+            bits |= libc::EPOLLERR;
+        }
+        if readiness & record::PRIORITY != 0 {
+            bits |= libc::EPOLLPRI;
+        }
+        let read_closed = readiness & record::READ_CLOSED != 0;
+        let write_closed = readiness & record::WRITE_CLOSED != 0;
+        if read_closed && write_closed {
+            bits |= libc::EPOLLHUP;
+        } else if read_closed {
+            bits |= libc::EPOLLIN | libc::EPOLLRDHUP;
+        } else if write_closed {
+            bits |= libc::EPOLLERR;
+        }
+        events.push(Event {
+            events: bits as u32,
+            u64: usize::from(token) as u64,
+        });
+    }
+
+    /// Returns the number of events `push` pushes for `readiness`.
+    pub fn count(_: u8) -> usize {
+        1
+    }
+
     pub fn token(event: &Event) -> Token {
         Token(event.u64 as usize)
     }
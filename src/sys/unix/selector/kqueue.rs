@@ -1,10 +1,14 @@
+use super::{RegistrationGuard, Registrations};
 use crate::{Interest, Token};
-use log::error;
+use log::{debug, error};
+use std::cell::RefCell;
 use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
 use std::os::unix::io::{AsRawFd, RawFd};
 #[cfg(debug_assertions)]
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{cmp, io, ptr, slice};
 
@@ -69,21 +73,37 @@ pub struct Selector {
     #[cfg(debug_assertions)]
     id: usize,
     kq: RawFd,
+    /// Only tracked if created with fork support, see `reinit`.
+    registrations: Option<Registrations>,
+    /// Token used in `setup_waker`, if called.
+    #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+    waker_token: Arc<Mutex<Option<Token>>>,
     #[cfg(debug_assertions)]
     has_waker: AtomicBool,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
-        syscall!(kqueue())
-            .and_then(|kq| syscall!(fcntl(kq, libc::F_SETFD, libc::FD_CLOEXEC)).map(|_| kq))
-            .map(|kq| Selector {
-                #[cfg(debug_assertions)]
-                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
-                kq,
-                #[cfg(debug_assertions)]
-                has_waker: AtomicBool::new(false),
-            })
+        Selector::with_registrations(None)
+    }
+
+    /// Create a new selector that keeps track of the registered file
+    /// descriptors, which is required by `reinit`.
+    pub fn with_fork_support() -> io::Result<Selector> {
+        Selector::with_registrations(Some(Registrations::default()))
+    }
+
+    fn with_registrations(registrations: Option<Registrations>) -> io::Result<Selector> {
+        new_kqueue().map(|kq| Selector {
+            #[cfg(debug_assertions)]
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            kq,
+            registrations,
+            #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+            waker_token: Arc::new(Mutex::new(None)),
+            #[cfg(debug_assertions)]
+            has_waker: AtomicBool::new(false),
+        })
     }
 
     pub fn try_clone(&self) -> io::Result<Selector> {
@@ -92,11 +112,54 @@ impl Selector {
             #[cfg(debug_assertions)]
             id: self.id,
             kq,
+            registrations: self.registrations.clone(),
+            #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+            waker_token: Arc::clone(&self.waker_token),
             #[cfg(debug_assertions)]
             has_waker: AtomicBool::new(self.has_waker.load(Ordering::Acquire)),
         })
     }
 
+    /// Replaces the kqueue with a new one, using the same file descriptor, and
+    /// registers all registered file descriptors (and the waker) with it.
+    ///
+    /// Returns an error if the selector wasn't created with fork support.
+    /// File descriptors that were closed without being deregistered are
+    /// skipped and forgotten.
+    pub fn reinit(&self) -> io::Result<()> {
+        let tracked = match self.registrations {
+            Some(ref registrations) => registrations,
+            None => return Err(super::no_fork_support()),
+        };
+        super::replace_fd(self.kq, new_kqueue()?)?;
+
+        #[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+        {
+            if let Some(token) = *self.waker_token.lock().unwrap() {
+                self.setup_waker(token)?;
+            }
+        }
+
+        // Copy the registrations as `register` updates them.
+        let registrations: Vec<(RawFd, (Token, Interest))> = tracked
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&fd, &registration)| (fd, registration))
+            .collect();
+        for (fd, (token, interests)) in registrations {
+            match self.register(fd, token, interests) {
+                Ok(()) => {}
+                Err(ref err) if super::is_closed_fd_error(err) => {
+                    debug!("not re-registering closed file descriptor: fd={}", fd);
+                    let _ = tracked.lock().unwrap().remove(&fd);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
     pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
         let timeout = timeout.map(|to| libc::timespec {
             tv_sec: cmp::min(to.as_secs(), libc::time_t::max_value() as u64) as libc::time_t,
@@ -163,7 +226,36 @@ impl Selector {
             // the array.
             slice::from_raw_parts_mut(changes[0].as_mut_ptr(), n_changes)
         };
-        kevent_register(self.kq, changes, &[libc::EPIPE as Data])
+        if add_to_batch(self.kq, changes, fd, (token, interests)) {
+            return Ok(());
+        }
+        kevent_register(self.kq, changes, &[libc::EPIPE as Data])?;
+        super::track(&self.registrations, fd, Some((token, interests)));
+        Ok(())
+    }
+
+    /// Start a batch of registrations, see [`Batch`].
+    ///
+    /// If a batch was already started on this thread the registrations are
+    /// added to that batch instead and submitting the returned batch does
+    /// nothing.
+    pub fn start_batch(&self) -> Batch<'_> {
+        let started = BATCH.with(|batch| {
+            let mut batch = batch.borrow_mut();
+            if batch.is_some() {
+                return false;
+            }
+            *batch = Some(BatchChanges {
+                kq: self.kq,
+                changes: Vec::new(),
+                registrations: Vec::new(),
+            });
+            true
+        });
+        Batch {
+            selector: self,
+            started,
+        }
     }
 
     pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
@@ -196,10 +288,14 @@ impl Selector {
             self.kq,
             &mut changes,
             &[libc::ENOENT as Data, libc::EPIPE as Data],
-        )
+        )?;
+        super::track(&self.registrations, fd, Some((token, interests)));
+        Ok(())
     }
 
     pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        super::track(&self.registrations, fd, None);
+
         let flags = libc::EV_DELETE | libc::EV_RECEIPT;
         let mut changes: [libc::kevent; 2] = [
             kevent!(fd, libc::EVFILT_WRITE, flags, 0),
@@ -214,6 +310,12 @@ impl Selector {
         kevent_register(self.kq, &mut changes, &[libc::ENOENT as Data])
     }
 
+    /// Returns a guard that stops tracking the registration of `fd` when
+    /// dropped, if the selector was created with fork support.
+    pub fn registration_guard(&self, fd: RawFd) -> Option<RegistrationGuard> {
+        super::registration_guard(&self.registrations, fd)
+    }
+
     #[cfg(debug_assertions)]
     pub fn register_waker(&self) -> bool {
         self.has_waker.swap(true, Ordering::AcqRel)
@@ -234,6 +336,7 @@ impl Selector {
             if (kevent.flags & libc::EV_ERROR) != 0 && kevent.data != 0 {
                 Err(io::Error::from_raw_os_error(kevent.data as i32))
             } else {
+                *self.waker_token.lock().unwrap() = Some(token);
                 Ok(())
             }
         })
@@ -260,6 +363,79 @@ impl Selector {
     }
 }
 
+/// Batch of registrations, submitted using a single `kevent` call.
+///
+/// While the batch is alive calls to `Selector::register` on the same thread
+/// only collect the changes for the selector. Dropping the batch without
+/// submitting it discards them.
+pub struct Batch<'a> {
+    selector: &'a Selector,
+    started: bool,
+}
+
+impl<'a> Batch<'a> {
+    /// Submit all changes collected by the batch.
+    pub fn submit(self) -> io::Result<()> {
+        if !self.started {
+            return Ok(());
+        }
+        let mut batch = match BATCH.with(|batch| batch.borrow_mut().take()) {
+            Some(batch) if !batch.changes.is_empty() => batch,
+            _ => return Ok(()),
+        };
+        // For the explanation of ignoring `EPIPE` see `Selector::register`.
+        kevent_register(self.selector.kq, &mut batch.changes, &[libc::EPIPE as Data])?;
+        for (fd, registration) in batch.registrations {
+            super::track(&self.selector.registrations, fd, Some(registration));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for Batch<'a> {
+    fn drop(&mut self) {
+        if self.started {
+            let _ = BATCH.with(|batch| batch.borrow_mut().take());
+        }
+    }
+}
+
+/// Changes collected by a `Batch`.
+struct BatchChanges {
+    /// The kqueue of the selector that started the batch.
+    kq: RawFd,
+    changes: Vec<libc::kevent>,
+    registrations: Vec<(RawFd, (Token, Interest))>,
+}
+
+thread_local! {
+    /// Batch started on this thread, if any.
+    static BATCH: RefCell<Option<BatchChanges>> = RefCell::new(None);
+}
+
+/// Add `changes` to the batch started on this thread, if it was started for
+/// `kq`. Returns false if no such batch was started.
+fn add_to_batch(
+    kq: RawFd,
+    changes: &[libc::kevent],
+    fd: RawFd,
+    registration: (Token, Interest),
+) -> bool {
+    BATCH.with(|batch| match *batch.borrow_mut() {
+        Some(ref mut batch) if batch.kq == kq => {
+            batch.changes.extend_from_slice(changes);
+            batch.registrations.push((fd, registration));
+            true
+        }
+        _ => false,
+    })
+}
+
+fn new_kqueue() -> io::Result<RawFd> {
+    syscall!(kqueue())
+        .and_then(|kq| syscall!(fcntl(kq, libc::F_SETFD, libc::FD_CLOEXEC)).map(|_| kq))
+}
+
 /// Register `changes` with `kq`ueue.
 fn kevent_register(
     kq: RawFd,
@@ -358,12 +534,70 @@ unsafe impl Send for Events {}
 unsafe impl Sync for Events {}
 
 pub mod event {
-    use std::fmt;
+    use std::{cmp, fmt};
 
-    use crate::sys::Event;
+    use crate::event::record;
+    use crate::sys::{Event, Events};
     use crate::Token;
 
-    use super::{Filter, Flags};
+    use super::{Filter, Flags, UData};
+
+    /// Readiness bits mapped to a kqueue filter, the filter and the readiness
+    /// bit that sets `EV_EOF` on the event.
+    const FILTERS: &[(u8, Filter, u8)] = &[
+        (
+            record::READABLE | record::READ_CLOSED,
+            libc::EVFILT_READ as Filter,
+            record::READ_CLOSED,
+        ),
+        (
+            record::WRITABLE | record::WRITE_CLOSED,
+            libc::EVFILT_WRITE as Filter,
+            record::WRITE_CLOSED,
+        ),
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "macos"
+        ))]
+        (record::AIO, libc::EVFILT_AIO as Filter, 0),
+        #[cfg(target_os = "freebsd")]
+        (record::LIO, libc::EVFILT_LIO as Filter, 0),
+    ];
+
+    /// Push events with `token` and the recorded `readiness` to `events`.
+    ///
+    /// A kqueue event has a single filter, so readable and writable readiness
+    /// are pushed as separate events. Readiness without a filter, e.g. only an
+    /// error, is pushed as an event without a filter.
+    pub fn push(events: &mut Events, token: Token, readiness: u8) {
+        let flags = if readiness & record::ERROR != 0 {
+            libc::EV_ERROR
+        } else {
+            0
+        };
+        let mut pushed = false;
+        for &(bits, filter, closed) in FILTERS {
+            if readiness & bits != 0 {
+                let eof = if readiness & closed != 0 { libc::EV_EOF } else { 0 };
+                events.push(kevent!(0, filter, flags | eof, token.0));
+                pushed = true;
+            }
+        }
+        if !pushed {
+            events.push(kevent!(0, 0, flags, token.0));
+        }
+    }
+
+    /// Returns the number of events `push` pushes for `readiness`.
+    pub fn count(readiness: u8) -> usize {
+        let count = FILTERS
+            .iter()
+            .filter(|&&(bits, _, _)| readiness & bits != 0)
+            .count();
+        cmp::max(count, 1)
+    }
 
     pub fn token(event: &Event) -> Token {
         Token(event.udata as usize)
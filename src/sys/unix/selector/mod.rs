@@ -12,7 +12,24 @@ mod epoll;
     target_os = "linux",
     target_os = "solaris"
 ))]
-pub(crate) use self::epoll::{event, Event, Events, Selector};
+pub(crate) use self::epoll::{event, Event, Events};
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub(crate) use self::epoll::Selector;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod poll;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod dispatch;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) use self::dispatch::Selector;
+
+cfg_io_source! {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub(crate) use self::dispatch::Rearm;
+}
 
 #[cfg(any(
     target_os = "dragonfly",
@@ -34,6 +51,93 @@ mod kqueue;
 ))]
 pub(crate) use self::kqueue::{event, Event, Events, Selector};
 
+use crate::{Interest, Token};
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::{Arc, Mutex};
+
+/// File descriptors registered with a `Selector` created with fork support,
+/// mapped to the token and interests they are registered with.
+///
+/// Shared between a `Selector` and its clones. Used by `Selector::reinit` to
+/// register all file descriptors with the newly created selector.
+type Registrations = Arc<Mutex<HashMap<RawFd, (Token, Interest)>>>;
+
+/// Removes a file descriptor from the `Registrations` of a `Selector` when
+/// dropped.
+///
+/// Owned by the owner of the file descriptor (e.g. `IoSourceState`) and
+/// dropped before the file descriptor is closed, so that `Selector::reinit`
+/// doesn't register a closed file descriptor, or another file that reused its
+/// number.
+#[derive(Debug)]
+pub(crate) struct RegistrationGuard {
+    registrations: Registrations,
+    fd: RawFd,
+}
+
+impl Drop for RegistrationGuard {
+    fn drop(&mut self) {
+        let _ = self.registrations.lock().unwrap().remove(&self.fd);
+    }
+}
+
+/// Sets the `registration` of `fd` in `registrations`, or removes it if
+/// `None`. Does nothing if the selector wasn't created with fork support.
+fn track(registrations: &Option<Registrations>, fd: RawFd, registration: Option<(Token, Interest)>) {
+    if let Some(registrations) = registrations {
+        let mut registrations = registrations.lock().unwrap();
+        match registration {
+            Some(registration) => {
+                let _ = registrations.insert(fd, registration);
+            }
+            None => {
+                let _ = registrations.remove(&fd);
+            }
+        }
+    }
+}
+
+/// Returns a `RegistrationGuard` for `fd`, if the selector was created with
+/// fork support.
+fn registration_guard(
+    registrations: &Option<Registrations>,
+    fd: RawFd,
+) -> Option<RegistrationGuard> {
+    registrations.as_ref().map(|registrations| RegistrationGuard {
+        registrations: Arc::clone(registrations),
+        fd,
+    })
+}
+
+/// Error returned by `Selector::reinit` if the selector wasn't created with
+/// fork support.
+fn no_fork_support() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "`Poll` instance not created with fork support, see `Poll::with_fork_support`",
+    )
+}
+
+/// Replaces the file description referred to by `fd` with the one referred to
+/// by `new_fd`, keeping the file descriptor number `fd` (with `FD_CLOEXEC`
+/// set). `new_fd` is closed.
+fn replace_fd(fd: RawFd, new_fd: RawFd) -> io::Result<()> {
+    let res = syscall!(dup2(new_fd, fd))
+        .and_then(|_| syscall!(fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)))
+        .map(|_| ());
+    let _ = syscall!(close(new_fd));
+    res
+}
+
+/// Whether or not `err` is returned for a file descriptor that was closed
+/// without being deregistered, e.g. one registered using `SourceFd`, see
+/// `Selector::reinit`.
+fn is_closed_fd_error(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::EBADF) || err.raw_os_error() == Some(libc::EPERM)
+}
+
 /// Lowest file descriptor used in `Selector::try_clone`.
 ///
 /// # Notes
@@ -0,0 +1,435 @@
+//! Selector backed by `poll(2)`, for environments where epoll is not
+//! available, e.g. due to seccomp filters.
+//!
+//! `poll(2)` is level-triggered, while Mio's API is edge-triggered. The
+//! edge-triggered behaviour is emulated in user space: once an event is
+//! returned for an interest (readable or writable) that interest is disarmed,
+//! i.e. no longer polled for, until it's re-armed. Registrations are re-armed
+//! when the source is reregistered or when an I/O operation on an `IoSource`
+//! returns a `WouldBlock` error (see `Rearm`), which mirrors when an
+//! edge-triggered selector would report a new event.
+//!
+//! The events are returned as `epoll_event`s, using the same bits as epoll,
+//! so the `event` module of the epoll selector can be used.
+
+#[cfg(debug_assertions)]
+use super::epoll::NEXT_ID;
+use crate::sys::Events;
+use crate::{Interest, Token};
+
+use log::error;
+use std::collections::HashMap;
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{cmp, io};
+
+#[derive(Debug)]
+pub struct Selector {
+    #[cfg(debug_assertions)]
+    id: usize,
+    state: Arc<State>,
+    #[cfg(debug_assertions)]
+    has_waker: AtomicBool,
+}
+
+#[derive(Debug)]
+struct State {
+    fds: Mutex<Fds>,
+    /// Read end of the pipe used to wake up a thread blocked in `poll(2)`
+    /// after the registrations change.
+    notify_read: RawFd,
+    /// Write end of the pipe above.
+    notify_write: RawFd,
+}
+
+#[derive(Debug)]
+struct Fds {
+    /// File descriptors passed to `poll(2)`. The first entry is always
+    /// `notify_read`, the others are in the same order as `registrations`.
+    poll_fds: Vec<libc::pollfd>,
+    registrations: Vec<Registration>,
+    /// Maps file descriptors to their index in `registrations`.
+    index: HashMap<RawFd, usize>,
+    /// Whether or not a thread is blocked in `poll(2)`.
+    polling: bool,
+}
+
+#[derive(Debug)]
+struct Registration {
+    fd: RawFd,
+    token: Token,
+    /// Only used to re-arm the registration of an `IoSource`.
+    #[cfg(any(feature = "net", all(unix, feature = "os-ext")))]
+    interests: Interest,
+    /// Whether or not `fd` is the eventfd of a `Waker`.
+    waker: bool,
+}
+
+impl Selector {
+    pub fn new() -> io::Result<Selector> {
+        let (notify_read, notify_write) = new_pipe()?;
+        Ok(Selector {
+            #[cfg(debug_assertions)]
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            state: Arc::new(State {
+                fds: Mutex::new(Fds {
+                    poll_fds: vec![libc::pollfd {
+                        fd: notify_read,
+                        events: libc::POLLIN,
+                        revents: 0,
+                    }],
+                    registrations: Vec::new(),
+                    index: HashMap::new(),
+                    polling: false,
+                }),
+                notify_read,
+                notify_write,
+            }),
+            #[cfg(debug_assertions)]
+            has_waker: AtomicBool::new(false),
+        })
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        Ok(Selector {
+            // It's the same selector, so we use the same id.
+            #[cfg(debug_assertions)]
+            id: self.id,
+            state: Arc::clone(&self.state),
+            #[cfg(debug_assertions)]
+            has_waker: AtomicBool::new(self.has_waker.load(Ordering::Acquire)),
+        })
+    }
+
+    /// Replaces the notify pipe, which is shared with the parent process after
+    /// `fork(2)`. The registrations are kept in user space, so they don't need
+    /// to be registered again.
+    pub fn reinit(&self) -> io::Result<()> {
+        let (notify_read, notify_write) = new_pipe()?;
+        super::replace_fd(self.state.notify_read, notify_read)?;
+        super::replace_fd(self.state.notify_write, notify_write)
+    }
+
+    pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+        // Same limit as used by the epoll selector.
+        #[cfg(target_pointer_width = "32")]
+        const MAX_SAFE_TIMEOUT: u128 = 1789569;
+        #[cfg(not(target_pointer_width = "32"))]
+        const MAX_SAFE_TIMEOUT: u128 = libc::c_int::MAX as u128;
+
+        let timeout = timeout
+            .map(|to| cmp::min(to.as_millis(), MAX_SAFE_TIMEOUT) as libc::c_int)
+            .unwrap_or(-1);
+
+        events.clear();
+
+        // Poll a copy of the file descriptors so that other threads can
+        // (de)register file descriptors while we're blocked in `poll(2)`.
+        let mut poll_fds = {
+            let mut fds = self.state.fds.lock().unwrap();
+            fds.polling = true;
+            fds.poll_fds.clone()
+        };
+        let res = syscall!(poll(
+            poll_fds.as_mut_ptr(),
+            poll_fds.len() as libc::nfds_t,
+            timeout,
+        ));
+        let mut fds = self.state.fds.lock().unwrap();
+        fds.polling = false;
+        if res? == 0 {
+            return Ok(());
+        }
+
+        if poll_fds[0].revents != 0 {
+            self.state.drain_notify();
+        }
+
+        for poll_fd in &poll_fds[1..] {
+            if poll_fd.revents == 0 {
+                continue;
+            }
+            if events.len() == events.capacity() {
+                // The remaining events are still armed, so they will be
+                // returned in the next call.
+                break;
+            }
+
+            // The file descriptor could have been deregistered while we were
+            // polling.
+            let index = match fds.index.get(&poll_fd.fd) {
+                Some(index) => *index,
+                None => continue,
+            };
+
+            if poll_fd.revents & libc::POLLNVAL != 0 {
+                // The file descriptor was closed without being deregistered,
+                // epoll drops those silently as well.
+                fds.remove(index);
+                continue;
+            }
+
+            // Only return readiness we're (still) interested in, the
+            // registration could have been disarmed or changed.
+            let armed = fds.poll_fds[index + 1].events;
+            let ready = poll_fd.revents & (armed | libc::POLLERR | libc::POLLHUP);
+            if ready == 0 {
+                continue;
+            }
+
+            let registration = &fds.registrations[index];
+            events.push(libc::epoll_event {
+                events: poll_to_epoll(ready),
+                u64: usize::from(registration.token) as u64,
+            });
+
+            if registration.waker {
+                // The `Waker` doesn't go through `IoSource`, so it's never
+                // re-armed. Instead we reset it and keep it armed.
+                drain(registration.fd);
+            } else {
+                let mut armed = armed;
+                if ready & (libc::POLLIN | libc::POLLPRI | libc::POLLRDHUP) != 0 {
+                    armed &= !(libc::POLLIN | libc::POLLRDHUP);
+                }
+                if ready & libc::POLLOUT != 0 {
+                    armed &= !libc::POLLOUT;
+                }
+                if ready & (libc::POLLERR | libc::POLLHUP) != 0 {
+                    armed = 0;
+                }
+                fds.arm(index, armed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Register the eventfd of a `Waker`, see `select`.
+    pub fn register_waker_fd(&self, fd: RawFd, token: Token) -> io::Result<()> {
+        self.add(fd, token, Interest::READABLE, true)
+    }
+
+    fn add(&self, fd: RawFd, token: Token, interests: Interest, waker: bool) -> io::Result<()> {
+        // Same error as epoll returns for invalid file descriptors.
+        syscall!(fcntl(fd, libc::F_GETFD))?;
+
+        let mut fds = self.state.fds.lock().unwrap();
+        if fds.index.contains_key(&fd) {
+            return Err(io::Error::from_raw_os_error(libc::EEXIST));
+        }
+        let index = fds.registrations.len();
+        fds.registrations.push(Registration {
+            fd,
+            token,
+            #[cfg(any(feature = "net", all(unix, feature = "os-ext")))]
+            interests,
+            waker,
+        });
+        fds.poll_fds.push(libc::pollfd {
+            fd,
+            events: interests_to_poll(interests),
+            revents: 0,
+        });
+        let _ = fds.index.insert(fd, index);
+        self.state.notify(&fds);
+        Ok(())
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn register_waker(&self) -> bool {
+        self.has_waker.swap(true, Ordering::AcqRel)
+    }
+}
+
+// Without the `net` and `os-ext` features these are only used by `SourceFd`,
+// which is unused then.
+#[cfg_attr(not(any(feature = "net", feature = "os-ext")), allow(dead_code))]
+impl Selector {
+    pub fn register(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        self.add(fd, token, interests, false)
+    }
+
+    pub fn reregister(&self, fd: RawFd, token: Token, interests: Interest) -> io::Result<()> {
+        let mut fds = self.state.fds.lock().unwrap();
+        let index = fds.find(fd)?;
+        let registration = &mut fds.registrations[index];
+        registration.token = token;
+        #[cfg(any(feature = "net", all(unix, feature = "os-ext")))]
+        {
+            registration.interests = interests;
+        }
+        fds.arm(index, interests_to_poll(interests));
+        self.state.notify(&fds);
+        Ok(())
+    }
+
+    pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        let mut fds = self.state.fds.lock().unwrap();
+        let index = fds.find(fd)?;
+        fds.remove(index);
+        self.state.notify(&fds);
+        Ok(())
+    }
+}
+
+cfg_io_source! {
+    impl Selector {
+        /// Returns a handle to re-arm the registration of `fd`.
+        pub fn rearm_handle(&self, fd: RawFd) -> Rearm {
+            Rearm {
+                state: Arc::clone(&self.state),
+                fd,
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        pub fn id(&self) -> usize {
+            self.id
+        }
+    }
+}
+
+/// Returns the read end of the notify pipe, it's not possible to wait for
+/// events on this file descriptor.
+impl AsRawFd for Selector {
+    fn as_raw_fd(&self) -> RawFd {
+        self.state.notify_read
+    }
+}
+
+impl State {
+    /// Wake up the thread blocked in `poll(2)`, if any, so it picks up the
+    /// changed registrations.
+    fn notify(&self, fds: &Fds) {
+        if fds.polling {
+            let buf = [1u8];
+            // If the pipe is full the polling thread will be woken up anyway.
+            let _ = syscall!(write(self.notify_write, buf.as_ptr().cast(), buf.len()));
+        }
+    }
+
+    fn drain_notify(&self) {
+        let mut buf = [0u8; 64];
+        while let Ok(n) = syscall!(read(self.notify_read, buf.as_mut_ptr().cast(), buf.len())) {
+            if n < buf.len() as isize {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        for fd in [self.notify_read, self.notify_write].iter() {
+            if let Err(err) = syscall!(close(*fd)) {
+                error!("error closing poll notify pipe: {}", err);
+            }
+        }
+    }
+}
+
+// Without the `net` and `os-ext` features this is only used by `SourceFd`,
+// which is unused then.
+#[cfg_attr(not(any(feature = "net", feature = "os-ext")), allow(dead_code))]
+impl Fds {
+    fn find(&self, fd: RawFd) -> io::Result<usize> {
+        match self.index.get(&fd) {
+            Some(index) => Ok(*index),
+            // Same error as epoll returns for unregistered file descriptors.
+            None => Err(io::Error::from_raw_os_error(libc::ENOENT)),
+        }
+    }
+}
+
+impl Fds {
+    /// Set the events polled for of the registration at `index`.
+    fn arm(&mut self, index: usize, events: libc::c_short) {
+        let poll_fd = &mut self.poll_fds[index + 1];
+        poll_fd.events = events;
+        // A negative file descriptor is ignored by `poll(2)`. Not polling at
+        // all also prevents the error and hang up events, which are always
+        // returned, from being returned again.
+        poll_fd.fd = if events == 0 {
+            -1
+        } else {
+            self.registrations[index].fd
+        };
+    }
+
+    fn remove(&mut self, index: usize) {
+        let registration = self.registrations.swap_remove(index);
+        let _ = self.poll_fds.swap_remove(index + 1);
+        let _ = self.index.remove(&registration.fd);
+        if let Some(moved) = self.registrations.get(index) {
+            let _ = self.index.insert(moved.fd, index);
+        }
+    }
+}
+
+cfg_io_source! {
+    /// Re-arms the registration of a file descriptor, see the module
+    /// documentation.
+    #[derive(Debug)]
+    pub struct Rearm {
+        state: Arc<State>,
+        fd: RawFd,
+    }
+
+    impl Rearm {
+        pub fn rearm(&self) {
+            let mut fds = self.state.fds.lock().unwrap();
+            if let Some(&index) = fds.index.get(&self.fd) {
+                let events = interests_to_poll(fds.registrations[index].interests);
+                if fds.poll_fds[index + 1].events != events {
+                    fds.arm(index, events);
+                    self.state.notify(&fds);
+                }
+            }
+        }
+    }
+}
+
+fn new_pipe() -> io::Result<(RawFd, RawFd)> {
+    let mut fds: [RawFd; 2] = [-1, -1];
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK))?;
+    Ok((fds[0], fds[1]))
+}
+
+/// Reset the counter of an eventfd.
+fn drain(fd: RawFd) {
+    let mut buf = [0u8; 8];
+    let _ = syscall!(read(fd, buf.as_mut_ptr().cast(), buf.len()));
+}
+
+fn interests_to_poll(interests: Interest) -> libc::c_short {
+    let mut events = 0;
+    if interests.is_readable() {
+        events |= libc::POLLIN | libc::POLLRDHUP;
+    }
+    if interests.is_writable() {
+        events |= libc::POLLOUT;
+    }
+    events
+}
+
+fn poll_to_epoll(revents: libc::c_short) -> u32 {
+    const MAPPING: [(libc::c_short, libc::c_int); 6] = [
+        (libc::POLLIN, libc::EPOLLIN),
+        (libc::POLLPRI, libc::EPOLLPRI),
+        (libc::POLLOUT, libc::EPOLLOUT),
+        (libc::POLLERR, libc::EPOLLERR),
+        (libc::POLLHUP, libc::EPOLLHUP),
+        (libc::POLLRDHUP, libc::EPOLLRDHUP),
+    ];
+
+    let mut events = 0;
+    for &(poll, epoll) in MAPPING.iter() {
+        if revents & poll != 0 {
+            events |= epoll;
+        }
+    }
+    events as u32
+}
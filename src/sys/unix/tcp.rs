@@ -7,7 +7,23 @@ use std::os::unix::io::{AsRawFd, FromRawFd};
 use std::time::Duration;
 
 use crate::net::TcpKeepalive;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use crate::net::TcpInfo;
+pub(crate) use crate::sys::unix::net::{get_only_v6, set_only_v6};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::sys::unix::net::new_socket_with_protocol;
 use crate::sys::unix::net::{new_socket, socket_addr, to_socket_addr};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) use crate::sys::unix::net::{
+    get_bind_device, get_busy_poll, get_mark, get_prefer_busy_poll, set_bind_device,
+    set_busy_poll, set_mark, set_prefer_busy_poll,
+};
 
 #[cfg(any(target_os = "openbsd", target_os = "netbsd"))]
 use libc::SO_KEEPALIVE as KEEPALIVE_TIME;
@@ -30,6 +46,20 @@ pub(crate) fn new_v6_socket() -> io::Result<TcpSocket> {
     new_socket(libc::AF_INET6, libc::SOCK_STREAM)
 }
 
+/// `IPPROTO_MPTCP` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPPROTO_MPTCP: libc::c_int = 262;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn new_mptcp_v4_socket() -> io::Result<TcpSocket> {
+    new_socket_with_protocol(libc::AF_INET, libc::SOCK_STREAM, IPPROTO_MPTCP)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn new_mptcp_v6_socket() -> io::Result<TcpSocket> {
+    new_socket_with_protocol(libc::AF_INET6, libc::SOCK_STREAM, IPPROTO_MPTCP)
+}
+
 pub(crate) fn bind(socket: TcpSocket, addr: SocketAddr) -> io::Result<()> {
     let (raw_addr, raw_addr_length) = socket_addr(&addr);
     syscall!(bind(socket, raw_addr.as_ptr(), raw_addr_length))?;
@@ -416,6 +446,532 @@ pub(crate) fn get_keepalive_retries(socket: TcpSocket) -> io::Result<Option<u32>
     Ok(Some(optval as u32))
 }
 
+/// `TCP_FASTOPEN_CONNECT` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_FASTOPEN_CONNECT: libc::c_int = 30;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_fastopen(socket: TcpSocket, queue_len: u32) -> io::Result<()> {
+    let queue_len = queue_len.try_into().ok().unwrap_or(i32::MAX);
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_FASTOPEN,
+        &(queue_len as libc::c_int) as *const _ as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_fastopen(socket: TcpSocket) -> io::Result<u32> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_FASTOPEN,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval as u32)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn connect_fastopen(socket: TcpSocket, addr: SocketAddr) -> io::Result<net::TcpStream> {
+    // With `TCP_FASTOPEN_CONNECT` set `connect(2)` doesn't send the SYN, it's
+    // sent by the first write together with the data (Linux 4.11+).
+    let val: libc::c_int = 1;
+    match syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_FASTOPEN_CONNECT,
+        &val as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    )) {
+        Ok(_) => {}
+        // Not supported by the kernel, fall back to a regular connect.
+        Err(ref err)
+            if err.raw_os_error() == Some(libc::ENOPROTOOPT)
+                || err.raw_os_error() == Some(libc::EOPNOTSUPP)
+                || err.raw_os_error() == Some(libc::EINVAL) => {}
+        Err(err) => return Err(err),
+    }
+    connect(socket, addr)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_user_timeout(socket: TcpSocket, timeout: Duration) -> io::Result<()> {
+    let millis = std::cmp::min(timeout.as_millis(), libc::c_int::MAX as u128) as libc::c_int;
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_USER_TIMEOUT,
+        &millis as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_user_timeout(socket: TcpSocket) -> io::Result<Duration> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_USER_TIMEOUT,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(Duration::from_millis(optval as u64))
+}
+
+/// `TCP_NOTSENT_LOWAT` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_NOTSENT_LOWAT: libc::c_int = 25;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_notsent_lowat(socket: TcpSocket, lowat: u32) -> io::Result<()> {
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_NOTSENT_LOWAT,
+        &lowat as *const u32 as *const libc::c_void,
+        size_of::<u32>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_notsent_lowat(socket: TcpSocket) -> io::Result<u32> {
+    let mut optval: u32 = 0;
+    let mut optlen = mem::size_of::<u32>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_NOTSENT_LOWAT,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval)
+}
+
+/// Prefix of `struct tcp_info`, up to `tcpi_delivery_rate` (Linux 4.9).
+/// Older kernels return a shorter struct, `libc` doesn't define it for all
+/// targets.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Default)]
+struct tcp_info {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    /// `tcpi_snd_wscale` and `tcpi_rcv_wscale` bitfields.
+    tcpi_wscale: u8,
+    /// `tcpi_delivery_rate_app_limited` and `tcpi_fastopen_client_fail`
+    /// bitfields.
+    tcpi_flags: u8,
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    tcpi_fackets: u32,
+    tcpi_last_data_sent: u32,
+    tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_total_retrans: u32,
+    tcpi_pacing_rate: u64,
+    tcpi_max_pacing_rate: u64,
+    tcpi_bytes_acked: u64,
+    tcpi_bytes_received: u64,
+    tcpi_segs_out: u32,
+    tcpi_segs_in: u32,
+    tcpi_notsent_bytes: u32,
+    tcpi_min_rtt: u32,
+    tcpi_data_segs_in: u32,
+    tcpi_data_segs_out: u32,
+    tcpi_delivery_rate: u64,
+}
+
+/// `TCP_LISTEN` value of `tcpi_state`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_LISTEN: u8 = 10;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let mut info = tcp_info::default();
+    let mut optlen = size_of::<tcp_info>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_INFO,
+        &mut info as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    if info.tcpi_state == TCP_LISTEN {
+        // For listeners the kernel reports the accept queue in the `unacked`
+        // and `sacked` fields.
+        return Ok(TcpInfo {
+            accept_queue_len: Some(info.tcpi_unacked),
+            accept_queue_max: Some(info.tcpi_sacked),
+            ..TcpInfo::default()
+        });
+    }
+
+    Ok(TcpInfo {
+        rtt: Some(Duration::from_micros(u64::from(info.tcpi_rtt))),
+        rtt_var: Some(Duration::from_micros(u64::from(info.tcpi_rttvar))),
+        cwnd: Some(info.tcpi_snd_cwnd),
+        retransmits: Some(info.tcpi_total_retrans),
+        // `tcpi_delivery_rate` is the last field, not set by older kernels.
+        delivery_rate: if optlen as usize == size_of::<tcp_info>() {
+            Some(info.tcpi_delivery_rate)
+        } else {
+            None
+        },
+        unacked: Some(info.tcpi_unacked),
+        accept_queue_len: None,
+        accept_queue_max: None,
+    })
+}
+
+/// Prefix of FreeBSD's `struct tcp_info`, up to `tcpi_snd_rexmitpack`. `libc`
+/// doesn't define it for all versions we support.
+#[cfg(target_os = "freebsd")]
+#[repr(C)]
+#[derive(Default)]
+struct tcp_info {
+    tcpi_state: u8,
+    __tcpi_ca_state: u8,
+    __tcpi_retransmits: u8,
+    __tcpi_probes: u8,
+    __tcpi_backoff: u8,
+    tcpi_options: u8,
+    /// `tcpi_snd_wscale` and `tcpi_rcv_wscale` bitfields.
+    tcpi_wscale: u8,
+    tcpi_rto: u32,
+    __tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    __tcpi_unacked: u32,
+    __tcpi_sacked: u32,
+    __tcpi_lost: u32,
+    __tcpi_retrans: u32,
+    __tcpi_fackets: u32,
+    __tcpi_last_data_sent: u32,
+    __tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    __tcpi_last_ack_recv: u32,
+    __tcpi_pmtu: u32,
+    __tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    __tcpi_advmss: u32,
+    __tcpi_reordering: u32,
+    __tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_snd_wnd: u32,
+    tcpi_snd_bwnd: u32,
+    tcpi_snd_nxt: u32,
+    tcpi_rcv_nxt: u32,
+    tcpi_toe_tid: u32,
+    tcpi_snd_rexmitpack: u32,
+}
+
+/// `TCP_INFO` isn't defined in all `libc` versions we support.
+#[cfg(target_os = "freebsd")]
+const TCP_INFO: libc::c_int = 32;
+/// `SO_LISTENQLEN` isn't defined in all `libc` versions we support.
+#[cfg(target_os = "freebsd")]
+const SO_LISTENQLEN: libc::c_int = 0x1012;
+/// `SO_LISTENQLIMIT` isn't defined in all `libc` versions we support.
+#[cfg(target_os = "freebsd")]
+const SO_LISTENQLIMIT: libc::c_int = 0x1011;
+
+/// `TCPS_LISTEN` value of `tcpi_state`.
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+const TCPS_LISTEN: u8 = 1;
+
+#[cfg(target_os = "freebsd")]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let mut info = tcp_info::default();
+    let mut optlen = size_of::<tcp_info>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_INFO,
+        &mut info as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    if info.tcpi_state == TCPS_LISTEN {
+        return Ok(TcpInfo {
+            accept_queue_len: Some(get_listen_queue_option(socket, SO_LISTENQLEN)?),
+            accept_queue_max: Some(get_listen_queue_option(socket, SO_LISTENQLIMIT)?),
+            ..TcpInfo::default()
+        });
+    }
+
+    Ok(TcpInfo {
+        rtt: Some(Duration::from_micros(u64::from(info.tcpi_rtt))),
+        rtt_var: Some(Duration::from_micros(u64::from(info.tcpi_rttvar))),
+        // FreeBSD reports the congestion window in bytes.
+        cwnd: if info.tcpi_snd_mss != 0 {
+            Some(info.tcpi_snd_cwnd / info.tcpi_snd_mss)
+        } else {
+            None
+        },
+        retransmits: Some(info.tcpi_snd_rexmitpack),
+        delivery_rate: None,
+        unacked: None,
+        accept_queue_len: None,
+        accept_queue_max: None,
+    })
+}
+
+#[cfg(target_os = "freebsd")]
+fn get_listen_queue_option(socket: TcpSocket, option: libc::c_int) -> io::Result<u32> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        option,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+    Ok(optval as u32)
+}
+
+/// `struct tcp_connection_info`, `libc` doesn't define it for all versions
+/// we support.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[repr(C)]
+#[derive(Default)]
+struct tcp_connection_info {
+    tcpi_state: u8,
+    tcpi_snd_wscale: u8,
+    tcpi_rcv_wscale: u8,
+    __pad1: u8,
+    tcpi_options: u32,
+    tcpi_flags: u32,
+    tcpi_rto: u32,
+    tcpi_maxseg: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_snd_wnd: u32,
+    tcpi_snd_sbbytes: u32,
+    tcpi_rcv_wnd: u32,
+    tcpi_rttcur: u32,
+    tcpi_srtt: u32,
+    tcpi_rttvar: u32,
+    /// TCP fast open bitfields.
+    tcpi_tfo: u32,
+    tcpi_txpackets: u64,
+    tcpi_txbytes: u64,
+    tcpi_txretransmitbytes: u64,
+    tcpi_rxpackets: u64,
+    tcpi_rxbytes: u64,
+    tcpi_rxoutoforderbytes: u64,
+    tcpi_txretransmitpackets: u64,
+}
+
+/// `TCP_CONNECTION_INFO` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const TCP_CONNECTION_INFO: libc::c_int = 0x106;
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub(crate) fn get_tcp_info(socket: TcpSocket) -> io::Result<TcpInfo> {
+    let mut info = tcp_connection_info::default();
+    let mut optlen = size_of::<tcp_connection_info>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_CONNECTION_INFO,
+        &mut info as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    // The accept queue isn't reported.
+    if info.tcpi_state == TCPS_LISTEN {
+        return Ok(TcpInfo::default());
+    }
+
+    Ok(TcpInfo {
+        rtt: Some(Duration::from_millis(u64::from(info.tcpi_srtt))),
+        rtt_var: Some(Duration::from_millis(u64::from(info.tcpi_rttvar))),
+        // The congestion window is reported in bytes.
+        cwnd: if info.tcpi_maxseg != 0 {
+            Some(info.tcpi_snd_cwnd / info.tcpi_maxseg)
+        } else {
+            None
+        },
+        retransmits: Some(info.tcpi_txretransmitpackets.try_into().unwrap_or(u32::MAX)),
+        delivery_rate: None,
+        unacked: None,
+        accept_queue_len: None,
+        accept_queue_max: None,
+    })
+}
+
+/// Maximum length of a congestion control algorithm name, including the
+/// terminating null byte.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_CA_NAME_MAX: usize = 16;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_congestion_control(socket: TcpSocket, name: &str) -> io::Result<()> {
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_CONGESTION,
+        name.as_ptr() as *const libc::c_void,
+        name.len() as libc::socklen_t,
+    ))
+    .map(|_| ())
+    .map_err(|err| match err.raw_os_error() {
+        // The algorithm isn't built in or loaded as module.
+        Some(libc::ENOENT) => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("congestion control algorithm `{}` is not available", name),
+        ),
+        _ => err,
+    })
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_congestion_control(socket: TcpSocket) -> io::Result<String> {
+    let mut buf = [0u8; TCP_CA_NAME_MAX];
+    let mut optlen = buf.len() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        libc::TCP_CONGESTION,
+        buf.as_mut_ptr() as *mut _,
+        &mut optlen,
+    ))?;
+
+    let name = &buf[..optlen as usize];
+    let name = name.split(|b| *b == 0).next().unwrap_or(name);
+    String::from_utf8(name.to_vec()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "congestion control algorithm name is not valid UTF-8",
+        )
+    })
+}
+
+/// `TCP_IS_MPTCP` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_IS_MPTCP: libc::c_int = 43;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_is_mptcp(socket: TcpSocket) -> io::Result<bool> {
+    // Plain TCP sockets, including MPTCP sockets that fell back to TCP,
+    // return zero.
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_TCP,
+        TCP_IS_MPTCP,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+/// `IP_BIND_ADDRESS_NO_PORT` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IP_BIND_ADDRESS_NO_PORT: libc::c_int = 24;
+/// `IP_LOCAL_PORT_RANGE` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IP_LOCAL_PORT_RANGE: libc::c_int = 51;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_bind_address_no_port(socket: TcpSocket, no_port: bool) -> io::Result<()> {
+    let val: libc::c_int = if no_port { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_IP,
+        IP_BIND_ADDRESS_NO_PORT,
+        &val as *const libc::c_int as *const libc::c_void,
+        size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_bind_address_no_port(socket: TcpSocket) -> io::Result<bool> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_IP,
+        IP_BIND_ADDRESS_NO_PORT,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_local_port_range(socket: TcpSocket, low: u16, high: u16) -> io::Result<()> {
+    // The lower bound is stored in the low 16 bits, the upper bound in the
+    // high 16 bits.
+    let val = u32::from(low) | (u32::from(high) << 16);
+    syscall!(setsockopt(
+        socket,
+        libc::IPPROTO_IP,
+        IP_LOCAL_PORT_RANGE,
+        &val as *const u32 as *const libc::c_void,
+        size_of::<u32>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn get_local_port_range(socket: TcpSocket) -> io::Result<(u16, u16)> {
+    let mut optval: u32 = 0;
+    let mut optlen = mem::size_of::<u32>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::IPPROTO_IP,
+        IP_LOCAL_PORT_RANGE,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok((optval as u16, (optval >> 16) as u16))
+}
+
 pub fn accept(listener: &net::TcpListener) -> io::Result<(net::TcpStream, SocketAddr)> {
     let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
     let mut length = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
@@ -1,9 +1,28 @@
-use crate::sys::unix::net::{new_ip_socket, socket_addr};
+pub(crate) use crate::sys::unix::net::{get_only_v6, set_only_v6};
+// These socket options are shared with TCP sockets.
+#[cfg(not(any(target_os = "solaris", target_os = "illumos")))]
+pub(crate) use crate::sys::unix::tcp::{get_reuseport, set_reuseport};
+pub(crate) use crate::sys::unix::tcp::{
+    get_recv_buffer_size, get_reuseaddr, get_send_buffer_size, set_recv_buffer_size,
+    set_reuseaddr, set_send_buffer_size,
+};
+use crate::net::RecvMeta;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::sys::unix::net::SocketAddrCRepr;
+use crate::sys::unix::net::{new_ip_socket, new_socket, socket_addr, to_socket_addr};
 
 use std::io;
 use std::mem;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::mem::MaybeUninit;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::{cmp, ptr};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::time::Duration;
 
 pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
     // Gives a warning for non Apple platforms.
@@ -24,13 +43,506 @@ pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
 }
 
 pub(crate) fn only_v6(socket: &net::UdpSocket) -> io::Result<bool> {
+    get_only_v6(socket.as_raw_fd())
+}
+
+pub(crate) type UdpSocket = libc::c_int;
+
+pub(crate) fn new_v4_socket() -> io::Result<UdpSocket> {
+    new_socket(libc::AF_INET, libc::SOCK_DGRAM)
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<UdpSocket> {
+    new_socket(libc::AF_INET6, libc::SOCK_DGRAM)
+}
+
+pub(crate) fn bind_socket(socket: UdpSocket, addr: SocketAddr) -> io::Result<net::UdpSocket> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(bind(socket, raw_addr.as_ptr(), raw_addr_length))?;
+    Ok(unsafe { net::UdpSocket::from_raw_fd(socket) })
+}
+
+pub(crate) fn connect_socket(socket: UdpSocket, addr: SocketAddr) -> io::Result<net::UdpSocket> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(connect(socket, raw_addr.as_ptr(), raw_addr_length))?;
+    Ok(unsafe { net::UdpSocket::from_raw_fd(socket) })
+}
+
+pub(crate) fn close(socket: UdpSocket) {
+    let _ = unsafe { libc::close(socket) };
+}
+
+pub(crate) fn set_broadcast(socket: UdpSocket, broadcast: bool) -> io::Result<()> {
+    let val: libc::c_int = if broadcast { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BROADCAST,
+        &val as *const libc::c_int as *const libc::c_void,
+        mem::size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn get_broadcast(socket: UdpSocket) -> io::Result<bool> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket,
+        libc::SOL_SOCKET,
+        libc::SO_BROADCAST,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_busy_poll(socket: &net::UdpSocket, dur: Duration) -> io::Result<()> {
+    super::net::set_busy_poll(socket.as_raw_fd(), dur)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn busy_poll(socket: &net::UdpSocket) -> io::Result<Duration> {
+    super::net::get_busy_poll(socket.as_raw_fd())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_prefer_busy_poll(socket: &net::UdpSocket, prefer: bool) -> io::Result<()> {
+    super::net::set_prefer_busy_poll(socket.as_raw_fd(), prefer)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn prefer_busy_poll(socket: &net::UdpSocket) -> io::Result<bool> {
+    super::net::get_prefer_busy_poll(socket.as_raw_fd())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn bind_device(socket: &net::UdpSocket, interface: &[u8]) -> io::Result<()> {
+    super::net::set_bind_device(socket.as_raw_fd(), interface)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn device(socket: &net::UdpSocket) -> io::Result<Option<Vec<u8>>> {
+    super::net::get_bind_device(socket.as_raw_fd())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_mark(socket: &net::UdpSocket, mark: u32) -> io::Result<()> {
+    super::net::set_mark(socket.as_raw_fd(), mark)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn mark(socket: &net::UdpSocket) -> io::Result<u32> {
+    super::net::get_mark(socket.as_raw_fd())
+}
+
+/// Maximum number of datagrams received or sent in a single `recvmmsg(2)` or
+/// `sendmmsg(2)` call, limits the stack space used for the message headers.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const BATCH_LEN: usize = 32;
+
+/// Returns an array of uninitialised values for a batch of datagrams.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn uninit_batch<T>() -> [MaybeUninit<T>; BATCH_LEN] {
+    // Safety: an array of `MaybeUninit`s doesn't require initialisation.
+    unsafe { MaybeUninit::uninit().assume_init() }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn recv_many<B: AsMut<[u8]>>(
+    socket: &net::UdpSocket,
+    bufs: &mut [B],
+    meta: &mut [RecvMeta],
+) -> io::Result<usize> {
+    let n = cmp::min(BATCH_LEN, cmp::min(bufs.len(), meta.len()));
+    // Only the first `n` elements of the arrays below are initialised.
+    let mut iovecs: [MaybeUninit<libc::iovec>; BATCH_LEN] = uninit_batch();
+    let mut addrs: [MaybeUninit<libc::sockaddr_storage>; BATCH_LEN] = uninit_batch();
+    let mut controls: [MaybeUninit<Control>; BATCH_LEN] = uninit_batch();
+    let mut msgs: [MaybeUninit<libc::mmsghdr>; BATCH_LEN] = uninit_batch();
+    for ((((buf, iovec), addr), control), msg) in bufs
+        .iter_mut()
+        .zip(iovecs.iter_mut())
+        .zip(addrs.iter_mut())
+        .zip(controls.iter_mut())
+        .zip(msgs.iter_mut())
+        .take(n)
+    {
+        let buf = buf.as_mut();
+        *iovec = MaybeUninit::new(libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        });
+        *addr = MaybeUninit::zeroed();
+        *control = MaybeUninit::new([0; CONTROL_LEN]);
+        // Safety: initialised above.
+        let msg_hdr = unsafe {
+            new_msghdr(
+                &mut *iovec.as_mut_ptr(),
+                &mut *addr.as_mut_ptr(),
+                &mut *control.as_mut_ptr(),
+            )
+        };
+        *msg = MaybeUninit::new(libc::mmsghdr { msg_hdr, msg_len: 0 });
+    }
+
+    let received = syscall!(recvmmsg(
+        socket.as_raw_fd(),
+        msgs[0].as_mut_ptr(),
+        n as libc::c_uint,
+        0,
+        ptr::null_mut(),
+    ))? as usize;
+
+    for ((msg, addr), meta) in msgs.iter().zip(addrs.iter()).zip(meta.iter_mut()).take(received) {
+        // Safety: initialised above and by `recvmmsg`.
+        unsafe {
+            let msg = &*msg.as_ptr();
+            set_meta(&msg.msg_hdr, msg.msg_len as usize, &*addr.as_ptr(), meta)?;
+        }
+    }
+    Ok(received)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_many<B: AsRef<[u8]>>(
+    socket: &net::UdpSocket,
+    bufs: &[B],
+    addrs: &[SocketAddr],
+) -> io::Result<usize> {
+    let n = cmp::min(BATCH_LEN, bufs.len());
+    // Only the first `n` elements of the arrays below are initialised.
+    let mut iovecs: [MaybeUninit<libc::iovec>; BATCH_LEN] = uninit_batch();
+    let mut raw_addrs: [MaybeUninit<(SocketAddrCRepr, libc::socklen_t)>; BATCH_LEN] =
+        uninit_batch();
+    let mut msgs: [MaybeUninit<libc::mmsghdr>; BATCH_LEN] = uninit_batch();
+    for ((((buf, addr), iovec), raw_addr), msg) in bufs
+        .iter()
+        .zip(addrs.iter())
+        .zip(iovecs.iter_mut())
+        .zip(raw_addrs.iter_mut())
+        .zip(msgs.iter_mut())
+        .take(n)
+    {
+        let buf = buf.as_ref();
+        *iovec = MaybeUninit::new(libc::iovec {
+            iov_base: buf.as_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        });
+        *raw_addr = MaybeUninit::new(socket_addr(addr));
+        // Safety: initialised above.
+        let (raw_addr, raw_addr_length) = unsafe { &*raw_addr.as_ptr() };
+        // `msghdr` has private padding fields on some targets.
+        let mut mmsghdr: libc::mmsghdr = unsafe { mem::zeroed() };
+        mmsghdr.msg_hdr.msg_name = raw_addr.as_ptr() as *mut libc::c_void;
+        mmsghdr.msg_hdr.msg_namelen = *raw_addr_length;
+        mmsghdr.msg_hdr.msg_iov = iovec.as_mut_ptr();
+        mmsghdr.msg_hdr.msg_iovlen = 1;
+        *msg = MaybeUninit::new(mmsghdr);
+    }
+
+    syscall!(sendmmsg(
+        socket.as_raw_fd(),
+        msgs[0].as_mut_ptr(),
+        n as libc::c_uint,
+        0,
+    ))
+    .map(|sent| sent as usize)
+}
+
+/// Receive a single datagram using `recvmsg(2)`, which unlike `recvfrom(2)`
+/// reports truncation and control messages.
+pub(crate) fn recv_msg(
+    socket: &net::UdpSocket,
+    buf: &mut [u8],
+    meta: &mut RecvMeta,
+) -> io::Result<()> {
+    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut control: Control = [0; CONTROL_LEN];
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let mut msg = new_msghdr(&mut iovec, &mut addr, &mut control);
+
+    let len = syscall!(recvmsg(socket.as_raw_fd(), &mut msg, 0))?;
+    // Safety: `recvmsg` initialised the message.
+    unsafe { set_meta(&msg, len as usize, &addr, meta) }
+}
+
+/// Number of `u64`s in a [`Control`] buffer, large enough for the `UDP_GRO`
+/// and packet info control messages.
+const CONTROL_LEN: usize = 16;
+
+/// Buffer for received control messages, aligned for `cmsghdr`.
+type Control = [u64; CONTROL_LEN];
+
+/// Returns a `msghdr` to receive a datagram into `iovec`.
+fn new_msghdr(
+    iovec: &mut libc::iovec,
+    addr: &mut libc::sockaddr_storage,
+    control: &mut Control,
+) -> libc::msghdr {
+    // `msghdr` has private padding fields on some targets.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = addr as *mut _ as *mut libc::c_void;
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    msg.msg_iov = iovec;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = mem::size_of::<Control>() as _;
+    msg
+}
+
+/// Sets `meta` based on a datagram of `len` bytes received using `msg`.
+///
+/// # Safety
+///
+/// `msg` and `addr` must be initialised by `recvmsg(2)` or `recvmmsg(2)`.
+unsafe fn set_meta(
+    msg: &libc::msghdr,
+    len: usize,
+    addr: &libc::sockaddr_storage,
+    meta: &mut RecvMeta,
+) -> io::Result<()> {
+    meta.len = len;
+    meta.addr = to_socket_addr(addr)?;
+    meta.truncated = msg.msg_flags & libc::MSG_TRUNC != 0;
+    meta.segment_size = None;
+    meta.dst_addr = None;
+    meta.interface_index = None;
+
+    let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+    while !cmsg.is_null() {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            let data = libc::CMSG_DATA(cmsg);
+            match ((*cmsg).cmsg_level, (*cmsg).cmsg_type) {
+                (libc::IPPROTO_UDP, UDP_GRO) => {
+                    let size = ptr::read_unaligned(data as *const libc::c_int);
+                    meta.segment_size = Some(size as usize);
+                }
+                (libc::IPPROTO_IP, libc::IP_PKTINFO) => {
+                    let info = ptr::read_unaligned(data as *const in_pktinfo);
+                    let addr = Ipv4Addr::from(info.ipi_addr.s_addr.to_ne_bytes());
+                    meta.dst_addr = Some(IpAddr::V4(addr));
+                    meta.interface_index = Some(info.ipi_ifindex as u32);
+                }
+                (libc::IPPROTO_IPV6, IPV6_PKTINFO) => {
+                    let info = ptr::read_unaligned(data as *const in6_pktinfo);
+                    let addr = Ipv6Addr::from(info.ipi6_addr.s6_addr);
+                    meta.dst_addr = Some(IpAddr::V6(addr));
+                    meta.interface_index = Some(info.ipi6_ifindex);
+                }
+                _ => {}
+            }
+        }
+        cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+    }
+    Ok(())
+}
+
+/// `UDP_SEGMENT` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const UDP_SEGMENT: libc::c_int = 103;
+/// `UDP_GRO` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const UDP_GRO: libc::c_int = 104;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_segmented(
+    socket: &net::UdpSocket,
+    buf: &[u8],
+    segment_size: u16,
+    target: SocketAddr,
+) -> io::Result<usize> {
+    send_with_cmsg(
+        socket,
+        buf,
+        target,
+        libc::IPPROTO_UDP,
+        UDP_SEGMENT,
+        segment_size,
+    )
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn send_msg(
+    socket: &net::UdpSocket,
+    buf: &[u8],
+    target: SocketAddr,
+    source: IpAddr,
+) -> io::Result<usize> {
+    match source {
+        IpAddr::V4(source) => {
+            let info = in_pktinfo {
+                ipi_ifindex: 0,
+                ipi_spec_dst: libc::in_addr {
+                    s_addr: u32::from_ne_bytes(source.octets()),
+                },
+                ipi_addr: libc::in_addr { s_addr: 0 },
+            };
+            send_with_cmsg(socket, buf, target, libc::IPPROTO_IP, libc::IP_PKTINFO, info)
+        }
+        IpAddr::V6(source) => {
+            let info = in6_pktinfo {
+                ipi6_addr: libc::in6_addr {
+                    s6_addr: source.octets(),
+                },
+                ipi6_ifindex: 0,
+            };
+            send_with_cmsg(socket, buf, target, libc::IPPROTO_IPV6, IPV6_PKTINFO, info)
+        }
+    }
+}
+
+/// Sends `buf` to `target` using `sendmsg(2)` with a single control message
+/// of type `cmsg_type` holding `data`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn send_with_cmsg<T>(
+    socket: &net::UdpSocket,
+    buf: &[u8],
+    target: SocketAddr,
+    cmsg_level: libc::c_int,
+    cmsg_type: libc::c_int,
+    data: T,
+) -> io::Result<usize> {
+    let (addr, addr_length) = socket_addr(&target);
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let mut control: Control = [0; CONTROL_LEN];
+    // `msghdr` has private padding fields on some targets.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = addr.as_ptr() as *mut libc::c_void;
+    msg.msg_namelen = addr_length;
+    msg.msg_iov = &mut iovec;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = unsafe { libc::CMSG_SPACE(mem::size_of::<T>() as _) } as _;
+
+    // Safety: `control` is large enough for the control messages we send.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = cmsg_level;
+        (*cmsg).cmsg_type = cmsg_type;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<T>() as _) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut T, data);
+    }
+
+    syscall!(sendmsg(socket.as_raw_fd(), &msg, 0)).map(|sent| sent as usize)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn supports_gso(socket: &net::UdpSocket) -> io::Result<bool> {
     let mut optval: libc::c_int = 0;
     let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    match syscall!(getsockopt(
+        socket.as_raw_fd(),
+        libc::IPPROTO_UDP,
+        UDP_SEGMENT,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    )) {
+        Ok(_) => Ok(true),
+        // Not supported before Linux 4.18.
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOPROTOOPT) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_gro(socket: &net::UdpSocket, gro: bool) -> io::Result<()> {
+    let val: libc::c_int = if gro { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket.as_raw_fd(),
+        libc::IPPROTO_UDP,
+        UDP_GRO,
+        &val as *const libc::c_int as *const libc::c_void,
+        mem::size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn gro(socket: &net::UdpSocket) -> io::Result<bool> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    syscall!(getsockopt(
+        socket.as_raw_fd(),
+        libc::IPPROTO_UDP,
+        UDP_GRO,
+        &mut optval as *mut _ as *mut _,
+        &mut optlen,
+    ))?;
+
+    Ok(optval != 0)
+}
+
+/// `IPV6_RECVPKTINFO` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_RECVPKTINFO: libc::c_int = 49;
+/// `IPV6_PKTINFO` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_PKTINFO: libc::c_int = 50;
+
+/// `in_pktinfo` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct in_pktinfo {
+    ipi_ifindex: libc::c_int,
+    ipi_spec_dst: libc::in_addr,
+    ipi_addr: libc::in_addr,
+}
+
+/// `in6_pktinfo` isn't defined in all `libc` versions we support.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct in6_pktinfo {
+    ipi6_addr: libc::in6_addr,
+    ipi6_ifindex: libc::c_uint,
+}
+
+/// Returns the level and name of the socket option that enables packet info
+/// control messages, based on the address family of `socket`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn pktinfo_option(socket: &net::UdpSocket) -> io::Result<(libc::c_int, libc::c_int)> {
+    match socket.local_addr()? {
+        SocketAddr::V4(..) => Ok((libc::IPPROTO_IP, libc::IP_PKTINFO)),
+        // Also reports the destination of IPv4 datagrams, as IPv4-mapped
+        // IPv6 address, on dual-stack sockets.
+        SocketAddr::V6(..) => Ok((libc::IPPROTO_IPV6, IPV6_RECVPKTINFO)),
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_recv_pktinfo(socket: &net::UdpSocket, pktinfo: bool) -> io::Result<()> {
+    let (level, name) = pktinfo_option(socket)?;
+    let val: libc::c_int = if pktinfo { 1 } else { 0 };
+    syscall!(setsockopt(
+        socket.as_raw_fd(),
+        level,
+        name,
+        &val as *const libc::c_int as *const libc::c_void,
+        mem::size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn recv_pktinfo(socket: &net::UdpSocket) -> io::Result<bool> {
+    let (level, name) = pktinfo_option(socket)?;
+    let mut optval: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
     syscall!(getsockopt(
         socket.as_raw_fd(),
-        libc::IPPROTO_IPV6,
-        libc::IPV6_V6ONLY,
+        level,
+        name,
         &mut optval as *mut _ as *mut _,
         &mut optlen,
     ))?;
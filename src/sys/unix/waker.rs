@@ -1,7 +1,7 @@
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod eventfd {
-    use crate::sys::Selector;
-    use crate::{Interest, Token};
+    use crate::sys::{RegistrationGuard, Selector};
+    use crate::Token;
 
     use std::fs::File;
     use std::io::{self, Read, Write};
@@ -15,6 +15,8 @@ mod eventfd {
     /// reset the count to 0, returning the count.
     #[derive(Debug)]
     pub struct Waker {
+        // Dropped before `fd` is closed.
+        _registration: Option<RegistrationGuard>,
         fd: File,
     }
 
@@ -24,9 +26,10 @@ mod eventfd {
                 // Turn the file descriptor into a file first so we're ensured
                 // it's closed when dropped, e.g. when register below fails.
                 let file = unsafe { File::from_raw_fd(fd) };
-                selector
-                    .register(fd, token, Interest::READABLE)
-                    .map(|()| Waker { fd: file })
+                selector.register_waker_fd(fd, token).map(|()| Waker {
+                    _registration: selector.registration_guard(fd),
+                    fd: file,
+                })
             })
         }
 
@@ -106,7 +109,7 @@ pub use self::kqueue::Waker;
     target_os = "solaris"
 ))]
 mod pipe {
-    use crate::sys::unix::Selector;
+    use crate::sys::unix::{RegistrationGuard, Selector};
     use crate::{Interest, Token};
 
     use std::fs::File;
@@ -119,6 +122,8 @@ mod pipe {
     /// if writing to it (waking) fails.
     #[derive(Debug)]
     pub struct Waker {
+        // Dropped before `receiver` is closed.
+        _registration: Option<RegistrationGuard>,
         sender: File,
         receiver: File,
     }
@@ -133,7 +138,11 @@ mod pipe {
             let receiver = unsafe { File::from_raw_fd(fds[0]) };
             selector
                 .register(fds[0], token, Interest::READABLE)
-                .map(|()| Waker { sender, receiver })
+                .map(|()| Waker {
+                    _registration: selector.registration_guard(fds[0]),
+                    sender,
+                    receiver,
+                })
         }
 
         pub fn wake(&self) -> io::Result<()> {
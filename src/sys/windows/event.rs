@@ -3,6 +3,7 @@ use std::fmt;
 use miow::iocp::CompletionStatus;
 
 use super::afd;
+use crate::event::record;
 use crate::Token;
 
 #[derive(Clone)]
@@ -55,6 +56,30 @@ pub(crate) const READ_CLOSED_FLAGS: u32 =
     afd::POLL_DISCONNECT | afd::POLL_ABORT | afd::POLL_CONNECT_FAIL;
 pub(crate) const WRITE_CLOSED_FLAGS: u32 = afd::POLL_ABORT | afd::POLL_CONNECT_FAIL;
 
+/// Push an event with `token` and the recorded `readiness` to `events`.
+pub fn push(events: &mut Events, token: Token, readiness: u8) {
+    let mut event = Event::new(token);
+    let mapping = [
+        (record::READABLE, afd::POLL_RECEIVE),
+        (record::WRITABLE, afd::POLL_SEND),
+        (record::ERROR, afd::POLL_CONNECT_FAIL),
+        (record::READ_CLOSED, afd::POLL_DISCONNECT),
+        (record::WRITE_CLOSED, afd::POLL_ABORT),
+        (record::PRIORITY, afd::POLL_RECEIVE_EXPEDITED),
+    ];
+    for &(bit, flags) in mapping.iter() {
+        if readiness & bit != 0 {
+            event.flags |= flags;
+        }
+    }
+    events.events.push(event);
+}
+
+/// Returns the number of events `push` pushes for `readiness`.
+pub fn count(_: u8) -> usize {
+    1
+}
+
 pub fn is_readable(event: &Event) -> bool {
     event.flags & READABLE_FLAGS != 0
 }
@@ -3,12 +3,17 @@ use std::mem;
 use std::net::SocketAddr;
 use std::sync::Once;
 
-use winapi::ctypes::c_int;
+use winapi::ctypes::{c_char, c_int};
+use winapi::shared::minwindef::{BOOL, FALSE, TRUE};
 use winapi::shared::in6addr::{in6_addr_u, IN6_ADDR};
 use winapi::shared::inaddr::{in_addr_S_un, IN_ADDR};
-use winapi::shared::ws2def::{ADDRESS_FAMILY, AF_INET, AF_INET6, SOCKADDR, SOCKADDR_IN};
-use winapi::shared::ws2ipdef::{SOCKADDR_IN6_LH_u, SOCKADDR_IN6_LH};
-use winapi::um::winsock2::{ioctlsocket, socket, FIONBIO, INVALID_SOCKET, SOCKET};
+use winapi::shared::ws2def::{
+    ADDRESS_FAMILY, AF_INET, AF_INET6, IPPROTO_IPV6, SOCKADDR, SOCKADDR_IN,
+};
+use winapi::shared::ws2ipdef::{SOCKADDR_IN6_LH_u, IPV6_V6ONLY, SOCKADDR_IN6_LH};
+use winapi::um::winsock2::{
+    getsockopt, ioctlsocket, setsockopt, socket, FIONBIO, INVALID_SOCKET, SOCKET, SOCKET_ERROR,
+};
 
 /// Initialise the network stack for Windows.
 pub(crate) fn init() {
@@ -44,6 +49,39 @@ pub(crate) fn new_socket(domain: c_int, socket_type: c_int) -> io::Result<SOCKET
     })
 }
 
+pub(crate) fn set_only_v6(socket: SOCKET, only_v6: bool) -> io::Result<()> {
+    let val: BOOL = if only_v6 { TRUE } else { FALSE };
+    syscall!(
+        setsockopt(
+            socket,
+            IPPROTO_IPV6 as c_int,
+            IPV6_V6ONLY as c_int,
+            &val as *const _ as *const c_char,
+            mem::size_of::<BOOL>() as c_int,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn get_only_v6(socket: SOCKET) -> io::Result<bool> {
+    let mut optval: BOOL = FALSE;
+    let mut optlen = mem::size_of::<BOOL>() as c_int;
+    syscall!(
+        getsockopt(
+            socket,
+            IPPROTO_IPV6 as c_int,
+            IPV6_V6ONLY as c_int,
+            &mut optval as *mut _ as *mut c_char,
+            &mut optlen,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )?;
+    Ok(optval != FALSE)
+}
+
 /// A type with the same memory layout as `SOCKADDR`. Used in converting Rust level
 /// SocketAddr* types into their system representation. The benefit of this specific
 /// type over using `SOCKADDR_STORAGE` is that this type is exactly as large as it
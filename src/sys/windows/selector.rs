@@ -357,11 +357,9 @@ impl Selector {
         })
     }
 
-    /// # Safety
-    ///
-    /// This requires a mutable reference to self because only a single thread
-    /// can poll IOCP at a time.
-    pub fn select(&mut self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
+    /// Only a single thread can poll IOCP at a time, this is ensured by
+    /// `Poll::poll` requiring a mutable reference.
+    pub fn select(&self, events: &mut Events, timeout: Option<Duration>) -> io::Result<()> {
         self.inner.select(events, timeout)
     }
 
@@ -20,6 +20,7 @@ use winapi::um::winsock2::{
 };
 
 use crate::net::TcpKeepalive;
+pub(crate) use crate::sys::windows::net::{get_only_v6, set_only_v6};
 use crate::sys::windows::net::{init, new_socket, socket_addr};
 
 pub(crate) type TcpSocket = SOCKET;
@@ -1,15 +1,24 @@
 use std::io;
-use std::mem::{self, MaybeUninit};
+use std::mem;
 use std::net::{self, SocketAddr};
 use std::os::windows::io::{AsRawSocket, FromRawSocket};
 use std::os::windows::raw::SOCKET as StdSocket; // winapi uses usize, stdlib uses u32/u64.
 
-use winapi::ctypes::c_int;
-use winapi::shared::ws2def::IPPROTO_IPV6;
-use winapi::shared::ws2ipdef::IPV6_V6ONLY;
-use winapi::um::winsock2::{bind as win_bind, closesocket, getsockopt, SOCKET_ERROR, SOCK_DGRAM};
+use winapi::ctypes::{c_char, c_int};
+use winapi::shared::minwindef::{BOOL, FALSE, TRUE};
+use winapi::um::winsock2::{
+    bind as win_bind, closesocket, connect as win_connect, getsockopt, setsockopt, PF_INET,
+    PF_INET6, SOCKET, SOCKET_ERROR, SOCK_DGRAM, SOL_SOCKET, SO_BROADCAST,
+};
 
-use crate::sys::windows::net::{init, new_ip_socket, socket_addr};
+use crate::net::RecvMeta;
+pub(crate) use crate::sys::windows::net::{get_only_v6, set_only_v6};
+// These socket options are shared with TCP sockets.
+pub(crate) use crate::sys::windows::tcp::{
+    get_recv_buffer_size, get_reuseaddr, get_send_buffer_size, set_recv_buffer_size,
+    set_reuseaddr, set_send_buffer_size,
+};
+use crate::sys::windows::net::{init, new_ip_socket, new_socket, socket_addr};
 
 pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
     init();
@@ -31,23 +40,91 @@ pub fn bind(addr: SocketAddr) -> io::Result<net::UdpSocket> {
 }
 
 pub(crate) fn only_v6(socket: &net::UdpSocket) -> io::Result<bool> {
-    let mut optval: MaybeUninit<c_int> = MaybeUninit::uninit();
-    let mut optlen = mem::size_of::<c_int>() as c_int;
+    get_only_v6(socket.as_raw_socket() as SOCKET)
+}
+
+pub(crate) type UdpSocket = SOCKET;
+
+pub(crate) fn new_v4_socket() -> io::Result<UdpSocket> {
+    init();
+    new_socket(PF_INET, SOCK_DGRAM)
+}
+
+pub(crate) fn new_v6_socket() -> io::Result<UdpSocket> {
+    init();
+    new_socket(PF_INET6, SOCK_DGRAM)
+}
 
+pub(crate) fn bind_socket(socket: UdpSocket, addr: SocketAddr) -> io::Result<net::UdpSocket> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(
+        win_bind(socket, raw_addr.as_ptr(), raw_addr_length),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )?;
+    Ok(unsafe { net::UdpSocket::from_raw_socket(socket as StdSocket) })
+}
+
+pub(crate) fn connect_socket(socket: UdpSocket, addr: SocketAddr) -> io::Result<net::UdpSocket> {
+    let (raw_addr, raw_addr_length) = socket_addr(&addr);
+    syscall!(
+        win_connect(socket, raw_addr.as_ptr(), raw_addr_length),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )?;
+    Ok(unsafe { net::UdpSocket::from_raw_socket(socket as StdSocket) })
+}
+
+pub(crate) fn close(socket: UdpSocket) {
+    let _ = unsafe { closesocket(socket) };
+}
+
+pub(crate) fn set_broadcast(socket: UdpSocket, broadcast: bool) -> io::Result<()> {
+    let val: BOOL = if broadcast { TRUE } else { FALSE };
+    syscall!(
+        setsockopt(
+            socket,
+            SOL_SOCKET,
+            SO_BROADCAST,
+            &val as *const _ as *const c_char,
+            mem::size_of::<BOOL>() as c_int,
+        ),
+        PartialEq::eq,
+        SOCKET_ERROR
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn get_broadcast(socket: UdpSocket) -> io::Result<bool> {
+    let mut optval: BOOL = FALSE;
+    let mut optlen = mem::size_of::<BOOL>() as c_int;
     syscall!(
         getsockopt(
-            socket.as_raw_socket() as usize,
-            IPPROTO_IPV6 as c_int,
-            IPV6_V6ONLY as c_int,
-            optval.as_mut_ptr().cast(),
+            socket,
+            SOL_SOCKET,
+            SO_BROADCAST,
+            &mut optval as *mut _ as *mut c_char,
             &mut optlen,
         ),
         PartialEq::eq,
         SOCKET_ERROR
     )?;
+    Ok(optval != FALSE)
+}
 
-    debug_assert_eq!(optlen as usize, mem::size_of::<c_int>());
-    // Safety: `getsockopt` initialised `optval` for us.
-    let optval = unsafe { optval.assume_init() };
-    Ok(optval != 0)
+/// Receive a single datagram, setting `meta`.
+pub(crate) fn recv_msg(
+    socket: &net::UdpSocket,
+    buf: &mut [u8],
+    meta: &mut RecvMeta,
+) -> io::Result<()> {
+    let (len, addr) = socket.recv_from(buf)?;
+    meta.len = len;
+    meta.addr = addr;
+    // Truncated datagrams return a `WSAEMSGSIZE` error instead.
+    meta.truncated = false;
+    meta.segment_size = None;
+    meta.dst_addr = None;
+    meta.interface_index = None;
+    Ok(())
 }
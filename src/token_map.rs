@@ -0,0 +1,296 @@
+use crate::event::Event;
+use crate::Token;
+
+use std::{fmt, mem};
+
+/// Number of low bits of a [`Token`] used for the index into the map, the
+/// remaining high bits hold the generation.
+#[cfg(target_pointer_width = "64")]
+const INDEX_BITS: usize = 32;
+#[cfg(not(target_pointer_width = "64"))]
+const INDEX_BITS: usize = 24;
+
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const MAX_GENERATION: usize = usize::MAX >> INDEX_BITS;
+
+/// Marks the end of the free list.
+const NO_FREE_SLOT: usize = usize::MAX;
+
+/// Allocates [`Token`]s and maps them to values, e.g. connection state.
+///
+/// `TokenMap` is a slab that hands out a `Token` for every inserted value.
+/// Slots of removed values are reused, but every token also contains a
+/// generation which is incremented each time a slot is freed. A stale token,
+/// e.g. the token of an event that was already in [`Events`] when the value
+/// was removed, will not resolve to the value that reused the slot, instead
+/// [`get`], [`resolve`] etc. return `None`.
+///
+/// The generation of tokens handed out by a `TokenMap` is never zero, which
+/// means that small constant tokens, such as `Token(0)` for a listener, never
+/// collide with tokens allocated by the map. On 64 bit platforms this holds
+/// for all tokens smaller than `1 << 32`, on other platforms for all tokens
+/// smaller than `1 << 24`.
+///
+/// [`Events`]: crate::Events
+/// [`get`]: TokenMap::get
+/// [`resolve`]: TokenMap::resolve
+///
+/// # Notes
+///
+/// Generations wrap around after `2^32 - 1` (64 bit platforms) or `2^8 - 1`
+/// (other platforms) removals from the same slot, after which a stale token
+/// could again match the value in the slot.
+///
+/// # Examples
+///
+#[cfg_attr(all(feature = "os-poll", feature = "net"), doc = "```")]
+#[cfg_attr(not(all(feature = "os-poll", feature = "net")), doc = "```ignore")]
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use mio::net::{TcpListener, TcpStream};
+/// use mio::{Events, Interest, Poll, Token, TokenMap};
+/// use std::io::Read;
+/// use std::time::Duration;
+///
+/// const LISTENER: Token = Token(0);
+///
+/// let mut poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+/// let mut connections: TokenMap<TcpStream> = TokenMap::new();
+///
+/// let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+/// poll.registry().register(&mut listener, LISTENER, Interest::READABLE)?;
+/// # let _client = std::net::TcpStream::connect(listener.local_addr()?)?;
+///
+/// poll.poll(&mut events, Some(Duration::from_millis(100)))?;
+/// for event in events.iter() {
+///     if event.token() == LISTENER {
+///         while let Ok((mut stream, _)) = listener.accept() {
+///             let token = connections.next_token();
+///             poll.registry().register(&mut stream, token, Interest::READABLE)?;
+///             connections.insert(stream);
+///         }
+///     } else if let Some(stream) = connections.resolve_mut(event) {
+///         // Events for connections that were already removed are skipped.
+///         let mut buf = [0; 1024];
+///         let _ = stream.read(&mut buf);
+///     }
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+pub struct TokenMap<T> {
+    slots: Vec<Slot<T>>,
+    /// Index of the first vacant slot, or `NO_FREE_SLOT`.
+    next_free: usize,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: usize,
+    state: SlotState<T>,
+}
+
+enum SlotState<T> {
+    Occupied(T),
+    /// Index of the next vacant slot, or `NO_FREE_SLOT`.
+    Vacant(usize),
+}
+
+impl<T> TokenMap<T> {
+    /// Create a new, empty `TokenMap`.
+    pub fn new() -> TokenMap<T> {
+        TokenMap::with_capacity(0)
+    }
+
+    /// Create a new, empty `TokenMap` with space for at least `capacity`
+    /// values.
+    pub fn with_capacity(capacity: usize) -> TokenMap<T> {
+        TokenMap {
+            slots: Vec::with_capacity(capacity),
+            next_free: NO_FREE_SLOT,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the token that the next call to [`insert`] will return.
+    ///
+    /// This is useful to register an [`event::Source`] before inserting it
+    /// into the map, see the [type documentation] for an example.
+    ///
+    /// [`insert`]: TokenMap::insert
+    /// [`event::Source`]: crate::event::Source
+    /// [type documentation]: TokenMap
+    pub fn next_token(&self) -> Token {
+        if self.next_free == NO_FREE_SLOT {
+            token(self.slots.len(), 1)
+        } else {
+            token(self.next_free, self.slots[self.next_free].generation)
+        }
+    }
+
+    /// Insert `value` into the map, returning the token allocated for it.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the map already contains the maximum number of values,
+    /// `2^32` on 64 bit platforms and `2^24` on other platforms.
+    pub fn insert(&mut self, value: T) -> Token {
+        if self.next_free == NO_FREE_SLOT {
+            let index = self.slots.len();
+            assert!(index <= INDEX_MASK, "TokenMap is full");
+            self.len += 1;
+            self.slots.push(Slot {
+                generation: 1,
+                state: SlotState::Occupied(value),
+            });
+            token(index, 1)
+        } else {
+            let index = self.next_free;
+            self.len += 1;
+            let slot = &mut self.slots[index];
+            match mem::replace(&mut slot.state, SlotState::Occupied(value)) {
+                SlotState::Vacant(next_free) => self.next_free = next_free,
+                SlotState::Occupied(_) => unreachable!("free list points to an occupied slot"),
+            }
+            token(index, slot.generation)
+        }
+    }
+
+    /// Returns `true` if `token` refers to a value in the map.
+    pub fn contains(&self, token: Token) -> bool {
+        self.get(token).is_some()
+    }
+
+    /// Returns a reference to the value for `token`, or `None` if the value
+    /// was removed or `token` wasn't allocated by this map.
+    pub fn get(&self, token: Token) -> Option<&T> {
+        let (index, generation) = split(token);
+        match self.slots.get(index) {
+            Some(Slot {
+                generation: g,
+                state: SlotState::Occupied(value),
+            }) if *g == generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value for `token`, or `None` if the
+    /// value was removed or `token` wasn't allocated by this map.
+    pub fn get_mut(&mut self, token: Token) -> Option<&mut T> {
+        let (index, generation) = split(token);
+        match self.slots.get_mut(index) {
+            Some(Slot {
+                generation: g,
+                state: SlotState::Occupied(value),
+            }) if *g == generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value the `event` is for.
+    ///
+    /// Same as calling [`get`] with the event's token.
+    ///
+    /// [`get`]: TokenMap::get
+    pub fn resolve(&self, event: &Event) -> Option<&T> {
+        self.get(event.token())
+    }
+
+    /// Returns a mutable reference to the value the `event` is for.
+    ///
+    /// Same as calling [`get_mut`] with the event's token.
+    ///
+    /// [`get_mut`]: TokenMap::get_mut
+    pub fn resolve_mut(&mut self, event: &Event) -> Option<&mut T> {
+        self.get_mut(event.token())
+    }
+
+    /// Remove the value for `token` from the map, returning it.
+    ///
+    /// After this call `token`, and any events still carrying it, no longer
+    /// resolve to a value. Note that the value should be deregistered from the
+    /// [`Registry`] it was registered with, if any.
+    ///
+    /// [`Registry`]: crate::Registry
+    pub fn remove(&mut self, token: Token) -> Option<T> {
+        let (index, generation) = split(token);
+        let slot = match self.slots.get_mut(index) {
+            Some(slot) if slot.generation == generation => slot,
+            _ => return None,
+        };
+
+        match mem::replace(&mut slot.state, SlotState::Vacant(self.next_free)) {
+            SlotState::Occupied(value) => {
+                slot.generation = if slot.generation == MAX_GENERATION {
+                    1
+                } else {
+                    slot.generation + 1
+                };
+                self.next_free = index;
+                self.len -= 1;
+                Some(value)
+            }
+            state @ SlotState::Vacant(_) => {
+                slot.state = state;
+                None
+            }
+        }
+    }
+
+    /// Returns an iterator over the tokens and values in the map.
+    pub fn iter(&self) -> impl Iterator<Item = (Token, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot.state {
+                SlotState::Occupied(ref value) => Some((token(index, slot.generation), value)),
+                SlotState::Vacant(_) => None,
+            })
+    }
+
+    /// Returns an iterator over the tokens and mutable references to the
+    /// values in the map.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Token, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot.state {
+                SlotState::Occupied(ref mut value) => Some((token(index, slot.generation), value)),
+                SlotState::Vacant(_) => None,
+            })
+    }
+}
+
+impl<T> Default for TokenMap<T> {
+    fn default() -> TokenMap<T> {
+        TokenMap::new()
+    }
+}
+
+impl<T> fmt::Debug for TokenMap<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+fn token(index: usize, generation: usize) -> Token {
+    Token((generation << INDEX_BITS) | index)
+}
+
+fn split(token: Token) -> (usize, usize) {
+    (token.0 & INDEX_MASK, token.0 >> INDEX_BITS)
+}
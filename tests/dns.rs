@@ -0,0 +1,444 @@
+#![cfg(all(feature = "os-poll", feature = "net"))]
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{self, IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio::net::dns::{Lookup, Resolver, ResolverConfig};
+use mio::{Events, Interest, Poll, Token};
+
+mod util;
+use util::{any_local_address, init, init_with_poll};
+
+const RESOLVER: Token = Token(0);
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+/// Response of the [`TestServer`].
+enum Reply {
+    Answer(Vec<IpAddr>),
+    /// Answer with the question for another name.
+    AnswerOther(&'static str, Vec<IpAddr>),
+    NameError,
+    Truncated,
+    Ignore,
+}
+
+/// Handler of the [`TestServer`], called with the queried name, type and
+/// whether or not the query was received over TCP.
+type Handler = dyn Fn(&str, u16, bool) -> Reply + Send + Sync;
+
+/// Stand-in name server listening on UDP and TCP on the same port.
+struct TestServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl TestServer {
+    fn start<F>(handler: F) -> TestServer
+    where
+        F: Fn(&str, u16, bool) -> Reply + Send + Sync + 'static,
+    {
+        let handler: Arc<Handler> = Arc::new(handler);
+        let stop = Arc::new(AtomicBool::new(false));
+        let socket = net::UdpSocket::bind(any_local_address()).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let listener = net::TcpListener::bind(addr).unwrap();
+
+        let udp_handler = handler.clone();
+        let udp_stop = stop.clone();
+        let udp = thread::spawn(move || {
+            socket
+                .set_read_timeout(Some(Duration::from_millis(20)))
+                .unwrap();
+            let mut buf = [0; 512];
+            while !udp_stop.load(Ordering::Relaxed) {
+                let (n, peer) = match socket.recv_from(&mut buf) {
+                    Ok(res) => res,
+                    Err(_) => continue,
+                };
+                if let Some(response) = respond(&*udp_handler, &buf[..n], false) {
+                    socket.send_to(&response, peer).unwrap();
+                }
+            }
+        });
+
+        let tcp_stop = stop.clone();
+        let tcp = thread::spawn(move || {
+            listener.set_nonblocking(true).unwrap();
+            while !tcp_stop.load(Ordering::Relaxed) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(_) => {
+                        thread::sleep(Duration::from_millis(10));
+                        continue;
+                    }
+                };
+                stream.set_nonblocking(false).unwrap();
+                let mut len = [0; 2];
+                stream.read_exact(&mut len).unwrap();
+                let mut query = vec![0; u16::from_be_bytes(len) as usize];
+                stream.read_exact(&mut query).unwrap();
+                if let Some(response) = respond(&*handler, &query, true) {
+                    stream
+                        .write_all(&(response.len() as u16).to_be_bytes())
+                        .unwrap();
+                    stream.write_all(&response).unwrap();
+                }
+            }
+        });
+
+        TestServer {
+            addr,
+            stop,
+            threads: vec![udp, tcp],
+        }
+    }
+
+    fn resolver(&self, timeout: Duration, attempts: u32) -> Resolver {
+        let config = ResolverConfig::new()
+            .with_nameserver(self.addr)
+            .with_timeout(timeout)
+            .with_attempts(attempts);
+        Resolver::new(config)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for handle in self.threads.drain(..) {
+            handle.join().unwrap();
+        }
+    }
+}
+
+/// Returns the response to `query`.
+fn respond(handler: &Handler, query: &[u8], tcp: bool) -> Option<Vec<u8>> {
+    // Parse the question.
+    let mut pos = 12;
+    let mut labels = Vec::new();
+    while query[pos] != 0 {
+        let len = query[pos] as usize;
+        labels.push(std::str::from_utf8(&query[pos + 1..pos + 1 + len]).unwrap());
+        pos += 1 + len;
+    }
+    let qtype = u16::from_be_bytes([query[pos + 1], query[pos + 2]]);
+    let mut question = query[12..pos + 5].to_vec();
+
+    let (flags, answers): (u16, Vec<IpAddr>) = match handler(&labels.join("."), qtype, tcp) {
+        Reply::Answer(addrs) => (0x8180, addrs),
+        Reply::AnswerOther(name, addrs) => {
+            question.clear();
+            for label in name.split('.') {
+                question.push(label.len() as u8);
+                question.extend_from_slice(label.as_bytes());
+            }
+            question.push(0);
+            question.extend_from_slice(&query[pos + 1..pos + 5]);
+            (0x8180, addrs)
+        }
+        Reply::NameError => (0x8183, Vec::new()),
+        Reply::Truncated => (0x8380, Vec::new()),
+        Reply::Ignore => return None,
+    };
+
+    let mut response = Vec::new();
+    response.extend_from_slice(&query[..2]);
+    response.extend_from_slice(&flags.to_be_bytes());
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&(answers.len() as u16).to_be_bytes());
+    response.extend_from_slice(&[0, 0, 0, 0]);
+    response.extend_from_slice(&question);
+    for addr in answers {
+        // Pointer to the name in the question, followed by the type, class
+        // and TTL.
+        response.extend_from_slice(&[0xc0, 12]);
+        match addr {
+            IpAddr::V4(ip) => {
+                response.extend_from_slice(&[0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                response.extend_from_slice(&ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                response.extend_from_slice(&[0, 28, 0, 1, 0, 0, 0, 60, 0, 16]);
+                response.extend_from_slice(&ip.octets());
+            }
+        }
+    }
+    Some(response)
+}
+
+/// Drive `resolver` until `lookup` is completed.
+fn resolve(
+    poll: &mut Poll,
+    events: &mut Events,
+    resolver: &mut Resolver,
+    lookup: Lookup,
+) -> io::Result<Vec<IpAddr>> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        while let Some((id, result)) = resolver.poll() {
+            if id == lookup {
+                return result;
+            }
+        }
+        assert!(Instant::now() < deadline, "lookup took too long");
+        poll.poll(events, resolver.timeout().or(Some(Duration::from_secs(1))))?;
+    }
+}
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+/// Answers `example.com` with `::1` and `127.0.0.1`.
+fn answer(name: &str, qtype: u16) -> Reply {
+    match (name, qtype) {
+        ("example.com", TYPE_AAAA) => Reply::Answer(vec![ip("::1")]),
+        ("example.com", TYPE_A) => Reply::Answer(vec![ip("127.0.0.1")]),
+        _ => Reply::NameError,
+    }
+}
+
+#[test]
+fn resolver_config() {
+    let config = ResolverConfig::new()
+        .with_resolv_conf(
+            "# Comment\n\
+             nameserver 10.0.0.1\n\
+             nameserver ::1 ; Comment\n\
+             nameserver invalid\n\
+             search example.com\n\
+             options ndots:2 timeout:3 attempts:4\n",
+        )
+        .with_hosts(
+            "127.0.0.1 localhost\n\
+             ::1 localhost ip6-localhost # Comment\n\
+             # 10.0.0.2 commented\n\
+             10.0.0.3 Example.Internal\n",
+        );
+    assert_eq!(
+        config.nameservers(),
+        &["10.0.0.1:53".parse().unwrap(), "[::1]:53".parse().unwrap()]
+    );
+    assert_eq!(config.timeout(), Duration::from_secs(3));
+    assert_eq!(config.attempts(), 4);
+    assert_eq!(
+        config.host("localhost").unwrap(),
+        &[ip("127.0.0.1"), ip("::1")]
+    );
+    assert_eq!(config.host("ip6-localhost").unwrap(), &[ip("::1")]);
+    assert_eq!(config.host("example.internal.").unwrap(), &[ip("10.0.0.3")]);
+    assert!(config.host("commented").is_none());
+}
+
+#[test]
+fn lookup_without_queries() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let config = ResolverConfig::new().with_host("example.internal", ip("10.0.0.1"));
+    let mut resolver = Resolver::new(config);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+
+    let lookup = resolver.lookup("Example.Internal.").unwrap();
+    assert_eq!(resolver.timeout(), Some(Duration::from_millis(0)));
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("10.0.0.1")]);
+
+    let lookup = resolver.lookup("::1").unwrap();
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("::1")]);
+
+    // No name servers to query.
+    let lookup = resolver.lookup("example.com").unwrap();
+    let err = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(resolver.poll().is_none());
+    assert!(resolver.timeout().is_none());
+
+    for name in &["", "a..b", &"a".repeat(64), &"a.".repeat(130)] {
+        let err = resolver.lookup(name).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn lookup_udp() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|name, qtype, tcp| {
+        assert!(!tcp);
+        answer(name, qtype)
+    });
+
+    let mut resolver = server.resolver(Duration::from_secs(5), 1);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+
+    let lookup1 = resolver.lookup("example.com").unwrap();
+    let lookup2 = resolver.lookup("unknown.example.com").unwrap();
+    assert_ne!(lookup1, lookup2);
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup1).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+    let err = resolve(&mut poll, &mut events, &mut resolver, lookup2).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn lookup_ignores_response_for_other_name() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|name, _, _| {
+        let addrs = vec![ip("::1"), ip("127.0.0.1")];
+        match name {
+            // Right id, but the wrong name.
+            "example.com" => Reply::AnswerOther("example.org", addrs),
+            // Names are compared case-insensitively.
+            _ => Reply::AnswerOther("example.com", addrs),
+        }
+    });
+
+    let timeout = Duration::from_millis(100);
+    let mut resolver = server.resolver(timeout, 1);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let lookup = resolver.lookup("example.com").unwrap();
+    let err = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    let lookup = resolver.lookup("Example.COM").unwrap();
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+}
+
+#[test]
+fn lookup_before_register() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|name, qtype, _| answer(name, qtype));
+
+    let mut resolver = server.resolver(Duration::from_secs(5), 1);
+    let lookup = resolver.lookup("example.com").unwrap();
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+}
+
+#[test]
+#[cfg(unix)]
+fn lookup_after_reinit_after_fork() {
+    init();
+    let mut poll = Poll::with_fork_support().unwrap();
+    let mut events = Events::with_capacity(16);
+    let server = TestServer::start(|name, qtype, _| answer(name, qtype));
+
+    let mut resolver = server.resolver(Duration::from_secs(5), 1);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    poll.reinit_after_fork().unwrap();
+
+    // The sockets of the queries are registered after the selector was
+    // replaced, they must be registered with the new one. Otherwise no event
+    // is received and the lookup is only completed after the timeout.
+    let lookup = resolver.lookup("example.com").unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert!(events.iter().any(|event| event.token() == RESOLVER));
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+}
+
+#[test]
+fn lookup_truncated_falls_back_to_tcp() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|name, qtype, tcp| {
+        if tcp {
+            answer(name, qtype)
+        } else {
+            Reply::Truncated
+        }
+    });
+
+    let mut resolver = server.resolver(Duration::from_secs(5), 1);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let lookup = resolver.lookup("example.com").unwrap();
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+}
+
+#[test]
+fn lookup_retries_after_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+    let queries = Mutex::new(HashMap::new());
+    let server = TestServer::start(move |name, qtype, _| {
+        // Ignore the first query for each type.
+        let mut queries = queries.lock().unwrap();
+        let count = queries.entry(qtype).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            Reply::Ignore
+        } else {
+            answer(name, qtype)
+        }
+    });
+
+    let timeout = Duration::from_millis(100);
+    let mut resolver = server.resolver(timeout, 2);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let start = Instant::now();
+    let lookup = resolver.lookup("example.com").unwrap();
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap();
+    assert!(start.elapsed() >= timeout);
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+}
+
+#[test]
+fn lookup_timeout() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|_, _, _| Reply::Ignore);
+
+    let timeout = Duration::from_millis(50);
+    let mut resolver = server.resolver(timeout, 2);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let start = Instant::now();
+    let lookup = resolver.lookup("example.com").unwrap();
+    let err = resolve(&mut poll, &mut events, &mut resolver, lookup).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() >= timeout * 2);
+}
+
+#[test]
+fn cancel_lookup() {
+    let (mut poll, mut events) = init_with_poll();
+    let server = TestServer::start(|name, qtype, _| answer(name, qtype));
+
+    let mut resolver = server.resolver(Duration::from_secs(5), 1);
+    poll.registry()
+        .register(&mut resolver, RESOLVER, Interest::READABLE)
+        .unwrap();
+    let lookup1 = resolver.lookup("example.com").unwrap();
+    let lookup2 = resolver.lookup("example.com").unwrap();
+    assert!(resolver.cancel(lookup1));
+    assert!(!resolver.cancel(lookup1));
+
+    let addrs = resolve(&mut poll, &mut events, &mut resolver, lookup2).unwrap();
+    assert_eq!(addrs, vec![ip("::1"), ip("127.0.0.1")]);
+    assert!(resolver.poll().is_none());
+    assert!(resolver.timeout().is_none());
+}
@@ -0,0 +1,173 @@
+#![cfg(all(feature = "event-loop", feature = "net"))]
+
+use std::io::{Read, Write};
+use std::net;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio::event::Event;
+use mio::event_loop::{EventLoop, Handler, NotifyError};
+use mio::net::TcpStream;
+use mio::{Interest, Token};
+
+mod util;
+use util::{any_local_address, assert_send, init};
+
+const ID1: Token = Token(1);
+
+#[derive(Default)]
+struct TestHandler {
+    ready: Vec<Token>,
+    messages: Vec<usize>,
+    timeouts: Vec<&'static str>,
+    ticks: usize,
+}
+
+impl Handler for TestHandler {
+    type Timeout = &'static str;
+    type Message = usize;
+
+    fn ready(&mut self, event_loop: &mut EventLoop<Self>, event: &Event) {
+        self.ready.push(event.token());
+        event_loop.shutdown();
+    }
+
+    fn notify(&mut self, event_loop: &mut EventLoop<Self>, msg: usize) {
+        self.messages.push(msg);
+        if msg == 0 {
+            event_loop.shutdown();
+        }
+    }
+
+    fn timeout(&mut self, event_loop: &mut EventLoop<Self>, timeout: &'static str) {
+        self.timeouts.push(timeout);
+        if timeout == "stop" {
+            event_loop.shutdown();
+        }
+    }
+
+    fn tick(&mut self, _: &mut EventLoop<Self>) {
+        self.ticks += 1;
+    }
+}
+
+#[test]
+fn is_send() {
+    assert_send::<EventLoop<TestHandler>>();
+    assert_send::<mio::event_loop::Sender<usize>>();
+}
+
+#[test]
+fn ready() {
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    event_loop
+        .registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+
+    let mut handler = TestHandler::default();
+    event_loop.run(&mut handler).unwrap();
+    assert!(!event_loop.is_running());
+    assert_eq!(handler.ready, vec![ID1]);
+    assert!(handler.ticks >= 1);
+}
+
+#[test]
+fn notify() {
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let sender = event_loop.channel();
+    let handle = thread::spawn(move || {
+        for n in (0..5).rev() {
+            sender.send(n).unwrap();
+        }
+    });
+
+    let mut handler = TestHandler::default();
+    event_loop.run(&mut handler).unwrap();
+    handle.join().unwrap();
+    assert_eq!(handler.messages, vec![4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn notify_many_messages() {
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+
+    // More messages than are handled in a single iteration.
+    let sender = event_loop.channel();
+    for n in (0..1000).rev() {
+        sender.send(n).unwrap();
+    }
+
+    let mut handler = TestHandler::default();
+    event_loop.run(&mut handler).unwrap();
+    assert_eq!(handler.messages.len(), 1000);
+    assert!(handler.ticks > 1);
+}
+
+#[test]
+fn send_after_drop() {
+    init();
+    let event_loop = EventLoop::<TestHandler>::new().unwrap();
+    let sender = event_loop.channel();
+    drop(event_loop);
+
+    match sender.send(1) {
+        Err(NotifyError::Closed(1)) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn timeouts() {
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let start = Instant::now();
+    event_loop.timeout("stop", Duration::from_millis(100));
+    event_loop.timeout("first", Duration::from_millis(10));
+    let cancelled = event_loop.timeout("cancelled", Duration::from_millis(50));
+    assert!(event_loop.clear_timeout(cancelled));
+    assert!(!event_loop.clear_timeout(cancelled));
+
+    let mut handler = TestHandler::default();
+    event_loop.run(&mut handler).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(handler.timeouts, vec!["first", "stop"]);
+}
+
+#[test]
+fn run_once() {
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    event_loop
+        .registry()
+        .register(&mut stream, ID1, Interest::READABLE)
+        .unwrap();
+
+    let mut handler = TestHandler::default();
+    event_loop
+        .run_once(&mut handler, Some(Duration::from_millis(10)))
+        .unwrap();
+    assert!(handler.ready.is_empty());
+    assert_eq!(handler.ticks, 1);
+
+    let (mut peer, _) = listener.accept().unwrap();
+    peer.write_all(b"hello").unwrap();
+    event_loop
+        .run_once(&mut handler, Some(Duration::from_millis(500)))
+        .unwrap();
+    assert_eq!(handler.ready, vec![ID1]);
+
+    let mut buf = [0; 16];
+    assert_eq!(stream.read(&mut buf).unwrap(), 5);
+}
@@ -1,13 +1,17 @@
 #![cfg(all(feature = "os-poll", feature = "net"))]
 
+use std::io::{self, Read, Write};
+use std::net;
 use std::time::Duration;
 
+use mio::event::ReadinessTracker;
 use mio::net::TcpStream;
-use mio::{event, Token, Waker};
+use mio::{event, Interest, Token, Waker};
 
 mod util;
-use util::init_with_poll;
+use util::{any_local_address, assert_would_block, expect_events, init_with_poll, ExpectEvent};
 
+const ID1: Token = Token(1);
 const WAKE_TOKEN: Token = Token(10);
 
 #[test]
@@ -40,3 +44,67 @@ fn events_all() {
     events.clear();
     assert!(events.is_empty());
 }
+
+#[test]
+fn readiness_tracker() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let mut tracker = ReadinessTracker::new();
+    assert!(!tracker.is_readable());
+    assert!(!tracker.is_writable());
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    events.iter().for_each(|event| tracker.update(event));
+    assert!(!tracker.is_readable());
+    assert!(tracker.is_writable());
+
+    peer.write_all(b"hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    events.iter().for_each(|event| tracker.update(event));
+    assert!(tracker.is_readable());
+
+    // Readiness remains set until `WouldBlock` is returned.
+    let mut buf = [0; 5];
+    assert_eq!(tracker.do_read(|| stream.read(&mut buf)).unwrap(), 5);
+    assert!(tracker.is_readable());
+    let mut buf = [0; 16];
+    assert_eq!(tracker.do_read(|| stream.read(&mut buf)).unwrap(), 6);
+    assert!(tracker.is_readable());
+    assert_would_block(tracker.do_read(|| stream.read(&mut buf)));
+    assert!(!tracker.is_readable());
+    assert!(tracker.is_writable());
+
+    // Other errors don't change the readiness.
+    let err = tracker
+        .do_write(|| -> io::Result<()> { Err(io::ErrorKind::Other.into()) })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert!(tracker.is_writable());
+    tracker.clear_writable();
+    assert!(!tracker.is_writable());
+
+    drop(peer);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    events.iter().for_each(|event| tracker.update(event));
+    assert!(tracker.is_readable());
+    assert_eq!(tracker.do_read(|| stream.read(&mut buf)).unwrap(), 0);
+}
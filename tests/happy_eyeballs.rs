@@ -0,0 +1,144 @@
+#![cfg(all(feature = "os-poll", feature = "net"))]
+
+use std::io;
+use std::net::{self, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio::net::{HappyEyeballs, TcpSocket, TcpStream};
+use mio::{Events, Interest, Poll, Token};
+
+mod util;
+use util::{any_local_address, any_local_ipv6_address, expect_events, init_with_poll, ExpectEvent};
+
+const ID1: Token = Token(1);
+const ID2: Token = Token(2);
+
+/// Drive `connector` to completion.
+fn connect(
+    poll: &mut Poll,
+    events: &mut Events,
+    connector: &mut HappyEyeballs,
+) -> io::Result<TcpStream> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        if let Some(stream) = connector.poll(poll.registry(), ID1)? {
+            return Ok(stream);
+        }
+        assert!(Instant::now() < deadline, "connecting took too long");
+        poll.poll(events, connector.timeout().or(Some(Duration::from_secs(1))))?;
+    }
+}
+
+/// Returns an address that refuses connections.
+fn closed_address(addr: SocketAddr) -> SocketAddr {
+    net::TcpListener::bind(addr).unwrap().local_addr().unwrap()
+}
+
+#[test]
+fn connect_first_address() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener_v6 = net::TcpListener::bind(any_local_ipv6_address()).unwrap();
+    let listener_v4 = net::TcpListener::bind(any_local_address()).unwrap();
+    let addr_v6 = listener_v6.local_addr().unwrap();
+    let addr_v4 = listener_v4.local_addr().unwrap();
+
+    let mut connector = HappyEyeballs::new(vec![addr_v6, addr_v4]);
+    let stream = connect(&mut poll, &mut events, &mut connector).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr_v6);
+    let (_, peer_addr) = listener_v6.accept().unwrap();
+    assert_eq!(peer_addr, stream.local_addr().unwrap());
+
+    let mut connector = HappyEyeballs::new(vec![addr_v4, addr_v6]);
+    let stream = connect(&mut poll, &mut events, &mut connector).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr_v4);
+    let (_, peer_addr) = listener_v4.accept().unwrap();
+    assert_eq!(peer_addr, stream.local_addr().unwrap());
+}
+
+#[test]
+fn connect_after_failed_attempt() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let addrs = vec![closed_address(any_local_ipv6_address()), addr];
+
+    // A failed attempt starts the next attempt without waiting for the delay.
+    let start = Instant::now();
+    let mut connector = HappyEyeballs::new(addrs).with_delay(Duration::from_secs(10));
+    let stream = connect(&mut poll, &mut events, &mut connector).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+}
+
+#[test]
+fn returned_stream_is_deregistered() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut connector = HappyEyeballs::new(vec![listener.local_addr().unwrap()]);
+    let mut stream = connect(&mut poll, &mut events, &mut connector).unwrap();
+
+    poll.registry()
+        .register(&mut stream, ID2, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::WRITABLE)],
+    );
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn connect_racing_attempts() {
+    let (mut poll, mut events) = init_with_poll();
+
+    // A listener with a full accept queue drops new connection attempts,
+    // causing them to hang.
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(any_local_address()).unwrap();
+    let full_listener = socket.listen(0).unwrap();
+    let full_addr = full_listener.local_addr().unwrap();
+    let mut queued = Vec::new();
+    for _ in 0..4 {
+        queued.push(TcpStream::connect(full_addr).unwrap());
+    }
+    thread::sleep(Duration::from_millis(50));
+
+    let listener = net::TcpListener::bind(any_local_ipv6_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let start = Instant::now();
+    let mut connector =
+        HappyEyeballs::new(vec![full_addr, addr]).with_delay(Duration::from_millis(100));
+    let stream = connect(&mut poll, &mut events, &mut connector).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert!(connector.timeout().is_none());
+}
+
+#[test]
+fn all_attempts_fail() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let addrs = vec![
+        closed_address(any_local_address()),
+        closed_address(any_local_ipv6_address()),
+    ];
+    let mut connector = HappyEyeballs::new(addrs);
+    let err = connect(&mut poll, &mut events, &mut connector).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn no_addresses() {
+    let (poll, _) = init_with_poll();
+
+    let mut connector = HappyEyeballs::new(Vec::new());
+    assert!(connector.timeout().is_none());
+    let err = connector.poll(poll.registry(), ID1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -3,7 +3,7 @@
 use std::net;
 use std::sync::{Arc, Barrier};
 use std::thread::{self, sleep};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{fmt, io};
 
 use mio::event::Source;
@@ -12,7 +12,8 @@ use mio::{event, Events, Interest, Poll, Registry, Token};
 
 mod util;
 use util::{
-    any_local_address, assert_send, assert_sync, expect_events, init, init_with_poll, ExpectEvent,
+    any_local_address, assert_send, assert_sync, expect_events, expect_no_events, init,
+    init_with_poll, ExpectEvent,
 };
 
 const ID1: Token = Token(1);
@@ -95,6 +96,48 @@ fn zero_duration_polls_events() {
     drop(listener);
 }
 
+#[test]
+fn busy_poll() {
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    poll.set_busy_poll(Some(Duration::from_millis(5))).unwrap();
+    assert_eq!(poll.busy_poll(), Some(Duration::from_millis(5)));
+
+    // Without any events we should spin, block and then return after the
+    // timeout.
+    let start = Instant::now();
+    poll.poll(&mut events, Some(Duration::from_millis(20)))
+        .unwrap();
+    assert!(events.is_empty());
+    // The remaining timeout is truncated to milliseconds after spinning, so
+    // allow some slack.
+    assert!(start.elapsed() >= Duration::from_millis(15));
+
+    // Timeouts shorter than the busy poll window should be respected.
+    let start = Instant::now();
+    poll.poll(&mut events, Some(Duration::from_millis(1)))
+        .unwrap();
+    assert!(events.is_empty());
+    assert!(start.elapsed() < Duration::from_millis(200));
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+
+    poll.set_busy_poll(None).unwrap();
+    assert_eq!(poll.busy_poll(), None);
+}
+
 #[test]
 fn poll_closes_fd() {
     init();
@@ -659,3 +702,150 @@ pub fn assert_error<T, E: fmt::Display>(result: Result<T, E>, expected_msg: &str
         ),
     }
 }
+
+#[test]
+#[cfg(unix)]
+fn reinit_after_fork() {
+    use std::os::unix::io::AsRawFd;
+
+    init();
+    let mut poll = Poll::with_fork_support().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    poll.registry()
+        .register(&mut listener, ID1, Interest::READABLE)
+        .unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, ID2, Interest::WRITABLE)
+        .unwrap();
+    let mut deregistered = UdpSocket::bind(any_local_address()).unwrap();
+    poll.registry()
+        .register(&mut deregistered, ID3, Interest::WRITABLE)
+        .unwrap();
+    poll.registry().deregister(&mut deregistered).unwrap();
+    // Closed without being deregistered, should be forgotten. The new socket
+    // likely reuses its file descriptor number, but isn't registered.
+    let mut closed = UdpSocket::bind(any_local_address()).unwrap();
+    poll.registry()
+        .register(&mut closed, ID3, Interest::WRITABLE)
+        .unwrap();
+    drop(closed);
+    let _reused = UdpSocket::bind(any_local_address()).unwrap();
+
+    let fd = poll.as_raw_fd();
+    poll.reinit_after_fork().unwrap();
+    assert_eq!(poll.as_raw_fd(), fd);
+
+    let mut expected = vec![ID1, ID2];
+    for _ in 0..3 {
+        poll.poll(&mut events, Some(Duration::from_millis(500)))
+            .unwrap();
+        for event in events.iter() {
+            assert_ne!(event.token(), ID3, "unexpected event: {:?}", event);
+            expected.retain(|token| *token != event.token());
+        }
+        if expected.is_empty() {
+            break;
+        }
+    }
+    assert!(expected.is_empty(), "missing events for: {:?}", expected);
+
+    // Sources can still be reregistered and deregistered.
+    poll.registry()
+        .reregister(&mut stream, ID2, Interest::READABLE)
+        .unwrap();
+    poll.registry().deregister(&mut listener).unwrap();
+    expect_no_events(&mut poll, &mut events);
+}
+
+#[test]
+#[cfg(unix)]
+fn reinit_after_fork_requires_fork_support() {
+    let (mut poll, _) = init_with_poll();
+    assert_error(poll.reinit_after_fork(), "not created with fork support");
+}
+
+#[test]
+#[cfg(unix)]
+fn reinit_after_fork_in_child() {
+    use std::io::{Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use std::{fs, panic};
+
+    /// Returns the read and write end of a new pipe.
+    fn pipe() -> (fs::File, fs::File) {
+        let mut fds = [-1; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe { (fs::File::from_raw_fd(fds[0]), fs::File::from_raw_fd(fds[1])) }
+    }
+
+    init();
+    let mut poll = Poll::with_fork_support().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    poll.registry()
+        .register(&mut listener, ID1, Interest::READABLE)
+        .unwrap();
+
+    // Pipes used to let the parent and child process wait on each other.
+    let (mut parent_wait, mut child_signal) = pipe();
+    let (mut child_wait, mut parent_signal) = pipe();
+
+    let pid = unsafe { libc::fork() };
+    assert!(pid != -1, "fork failed: {}", io::Error::last_os_error());
+    if pid == 0 {
+        // Don't run the test harness in the child, only report the result.
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(move || {
+            drop(parent_wait);
+            drop(parent_signal);
+            poll.reinit_after_fork().unwrap();
+
+            // The listener is registered with the new selector...
+            let _stream = net::TcpStream::connect(addr).unwrap();
+            poll.poll(&mut events, Some(Duration::from_secs(1)))
+                .unwrap();
+            assert!(events.iter().any(|event| event.token() == ID1));
+
+            // ... and changes only affect the child's selector.
+            poll.registry().deregister(&mut listener).unwrap();
+            let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+            poll.registry()
+                .register(&mut socket, ID2, Interest::WRITABLE)
+                .unwrap();
+
+            child_signal.write_all(&[1]).unwrap();
+            let mut buf = [0; 1];
+            child_wait.read_exact(&mut buf).unwrap();
+        }));
+        unsafe { libc::_exit(if result.is_ok() { 0 } else { 1 }) };
+    }
+    drop(child_signal);
+
+    let mut buf = [0; 1];
+    parent_wait.read_exact(&mut buf).unwrap();
+    // The child's connection, the listener is still registered in the parent.
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    // The socket registered in the child isn't registered with the parent.
+    poll.poll(&mut events, Some(Duration::from_millis(100)))
+        .unwrap();
+    assert!(events.is_empty(), "unexpected events: {:?}", events);
+
+    parent_signal.write_all(&[1]).unwrap();
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(
+        libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+        "child process failed: status={}",
+        status
+    );
+}
@@ -0,0 +1,257 @@
+#![cfg(all(
+    feature = "os-poll",
+    feature = "net",
+    any(target_os = "android", target_os = "linux")
+))]
+
+//! Tests run against all backends, checking they behave the same.
+
+use std::io::{self, Read, Write};
+use std::net;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio::net::{TcpStream, UdpSocket};
+use mio::{Backend, Events, Interest, Poll, Token, Waker};
+
+mod util;
+use util::{
+    any_local_address, assert_would_block, expect_events, expect_no_events, init, ExpectEvent,
+};
+
+const BACKENDS: [Backend; 2] = [Backend::Epoll, Backend::Poll];
+
+const ID1: Token = Token(1);
+const ID2: Token = Token(2);
+const WAKER: Token = Token(10);
+
+fn poll(backend: Backend) -> (Poll, Events) {
+    init();
+    let poll = Poll::with_backend(backend).unwrap();
+    (poll, Events::with_capacity(16))
+}
+
+#[test]
+fn tcp_stream_events() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        poll.registry()
+            .register(&mut stream, ID1, Interest::READABLE)
+            .unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+
+        let mut buf = [0; 16];
+        for _ in 0..3 {
+            peer.write_all(b"hello").unwrap();
+            expect_events(
+                &mut poll,
+                &mut events,
+                vec![ExpectEvent::new(ID1, Interest::READABLE)],
+            );
+            // Drain the readiness, after which we should get a new event.
+            assert_eq!(stream.read(&mut buf).unwrap(), 5);
+            assert_would_block(stream.read(&mut buf));
+        }
+
+        // Without new data there should be no more events.
+        expect_no_events(&mut poll, &mut events);
+
+        drop(peer);
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID1, Interest::READABLE)],
+        );
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+}
+
+#[test]
+fn udp_socket_events() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+
+        let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+        let socket2 = net::UdpSocket::bind(any_local_address()).unwrap();
+        poll.registry()
+            .register(&mut socket1, ID1, Interest::READABLE)
+            .unwrap();
+
+        let mut buf = [0; 16];
+        for _ in 0..3 {
+            socket2
+                .send_to(b"hello", socket1.local_addr().unwrap())
+                .unwrap();
+            expect_events(
+                &mut poll,
+                &mut events,
+                vec![ExpectEvent::new(ID1, Interest::READABLE)],
+            );
+            assert_eq!(socket1.recv(&mut buf).unwrap(), 5);
+            assert_would_block(socket1.recv(&mut buf));
+        }
+    }
+}
+
+#[test]
+fn reregister_and_deregister() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        poll.registry()
+            .register(&mut stream, ID1, Interest::READABLE)
+            .unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        expect_no_events(&mut poll, &mut events);
+
+        poll.registry()
+            .reregister(&mut stream, ID2, Interest::WRITABLE)
+            .unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID2, Interest::WRITABLE)],
+        );
+
+        // Reregistering re-arms the registration.
+        poll.registry()
+            .reregister(&mut stream, ID1, Interest::WRITABLE)
+            .unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+        );
+
+        poll.registry().deregister(&mut stream).unwrap();
+        peer.write_all(b"hello").unwrap();
+        expect_no_events(&mut poll, &mut events);
+
+        // Errors match as well.
+        let err = poll.registry().deregister(&mut stream).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
+
+#[test]
+fn waker() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+
+        let waker = Waker::new(poll.registry(), WAKER).unwrap();
+        for _ in 0..3 {
+            waker.wake().unwrap();
+            expect_events(
+                &mut poll,
+                &mut events,
+                vec![ExpectEvent::new(WAKER, Interest::READABLE)],
+            );
+            expect_no_events(&mut poll, &mut events);
+        }
+    }
+}
+
+#[test]
+fn register_while_polling() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+
+        let registry = poll.registry().try_clone().unwrap();
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let barrier = Arc::new(Barrier::new(2));
+
+        let thread_barrier = barrier.clone();
+        let handle = thread::spawn(move || {
+            thread_barrier.wait();
+            // Give the other thread some time to start polling.
+            thread::sleep(Duration::from_millis(50));
+            let mut stream = TcpStream::connect(addr).unwrap();
+            registry
+                .register(&mut stream, ID1, Interest::WRITABLE)
+                .unwrap();
+            stream
+        });
+
+        barrier.wait();
+        let start = Instant::now();
+        loop {
+            poll.poll(&mut events, Some(Duration::from_secs(5)))
+                .unwrap();
+            if events.iter().any(|event| event.token() == ID1) {
+                break;
+            }
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let _stream = handle.join().unwrap();
+    }
+}
+
+#[test]
+fn events_capacity() {
+    for backend in BACKENDS.iter().copied() {
+        init();
+        let mut poll = Poll::with_backend(backend).unwrap();
+        let mut events = Events::with_capacity(1);
+
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut stream1 = TcpStream::connect(addr).unwrap();
+        let mut stream2 = TcpStream::connect(addr).unwrap();
+        poll.registry()
+            .register(&mut stream1, ID1, Interest::WRITABLE)
+            .unwrap();
+        poll.registry()
+            .register(&mut stream2, ID2, Interest::WRITABLE)
+            .unwrap();
+
+        // Events that don't fit are returned in the next call.
+        let mut tokens = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while tokens.len() < 2 && Instant::now() < deadline {
+            poll.poll(&mut events, Some(Duration::from_millis(100)))
+                .unwrap();
+            assert!(events.iter().count() <= 1);
+            tokens.extend(events.iter().map(|event| event.token()));
+        }
+        tokens.sort();
+        assert_eq!(tokens, vec![ID1, ID2]);
+    }
+}
+
+#[test]
+fn reinit_after_fork() {
+    for backend in BACKENDS.iter().copied() {
+        let (mut poll, mut events) = poll(backend);
+        if backend == Backend::Epoll {
+            // Epoll only keeps track of the registrations if asked to.
+            assert!(poll.reinit_after_fork().is_err());
+            poll = Poll::with_fork_support().unwrap();
+        }
+
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        poll.registry()
+            .register(&mut stream, ID1, Interest::WRITABLE)
+            .unwrap();
+        let waker = Waker::new(poll.registry(), WAKER).unwrap();
+
+        poll.reinit_after_fork().unwrap();
+
+        waker.wake().unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![
+                ExpectEvent::new(ID1, Interest::WRITABLE),
+                ExpectEvent::new(WAKER, Interest::READABLE),
+            ],
+        );
+    }
+}
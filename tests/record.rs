@@ -0,0 +1,186 @@
+#![cfg(all(feature = "os-poll", feature = "net"))]
+
+use std::io::{self, Cursor, Write};
+use std::net;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use mio::event::{Event, Replay};
+use mio::net::TcpStream;
+use mio::{Events, Interest, Token, Waker};
+
+mod util;
+use util::{any_local_address, init_with_poll};
+
+const ID1: Token = Token(1);
+const ID2: Token = Token(2);
+const WAKER: Token = Token(10);
+
+/// Writer that can be inspected while it's owned by `Poll`.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Token and readiness of an event.
+fn details(event: &Event) -> (Token, [bool; 6]) {
+    (
+        event.token(),
+        [
+            event.is_readable(),
+            event.is_writable(),
+            event.is_error(),
+            event.is_read_closed(),
+            event.is_write_closed(),
+            event.is_priority(),
+        ],
+    )
+}
+
+#[test]
+fn record_and_replay() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let buf = SharedBuf::default();
+    poll.record(buf.clone()).unwrap();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    let (peer, _) = listener.accept().unwrap();
+    let waker = Waker::new(poll.registry(), WAKER).unwrap();
+
+    let mut batches = Vec::new();
+    let timeout = Some(Duration::from_millis(100));
+    poll.poll(&mut events, timeout).unwrap();
+    batches.push(events.iter().map(details).collect::<Vec<_>>());
+    waker.wake().unwrap();
+    poll.poll(&mut events, timeout).unwrap();
+    batches.push(events.iter().map(details).collect());
+    drop(peer);
+    poll.poll(&mut events, timeout).unwrap();
+    batches.push(events.iter().map(details).collect());
+    // Empty batch.
+    poll.poll(&mut events, Some(Duration::from_millis(10)))
+        .unwrap();
+    batches.push(events.iter().map(details).collect());
+    assert!(poll.stop_recording().is_some());
+    assert!(poll.stop_recording().is_none());
+
+    // Not recorded.
+    poll.poll(&mut events, Some(Duration::from_millis(10)))
+        .unwrap();
+
+    let recording = buf.0.lock().unwrap().clone();
+    let mut replay = Replay::new(Cursor::new(recording)).unwrap();
+    let mut last_elapsed = Duration::from_millis(0);
+    for batch in batches {
+        let elapsed = replay.poll(&mut events).unwrap().unwrap();
+        assert!(elapsed >= last_elapsed);
+        last_elapsed = elapsed;
+        let replayed: Vec<_> = events.iter().map(details).collect();
+        assert_eq!(replayed, batch);
+    }
+    assert!(replay.poll(&mut events).unwrap().is_none());
+    assert!(events.is_empty());
+}
+
+#[test]
+fn replay_invalid_recording() {
+    let err = Replay::new(Cursor::new(b"not a recording")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = Replay::new(Cursor::new(b"mio")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Truncated batch.
+    let mut recording = b"mio-rec\x01".to_vec();
+    recording.extend_from_slice(&0u64.to_le_bytes());
+    recording.extend_from_slice(&1u32.to_le_bytes());
+    recording.extend_from_slice(&1u64.to_le_bytes());
+    let mut replay = Replay::new(Cursor::new(recording)).unwrap();
+    let mut events = Events::with_capacity(8);
+    let err = replay.poll(&mut events).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn replay_events_capacity() {
+    let mut recording = b"mio-rec\x01".to_vec();
+    recording.extend_from_slice(&0u64.to_le_bytes());
+    recording.extend_from_slice(&2u32.to_le_bytes());
+    for token in [ID1, ID2].iter() {
+        recording.extend_from_slice(&(token.0 as u64).to_le_bytes());
+        recording.push(1); // Readable.
+    }
+
+    let mut replay = Replay::new(Cursor::new(recording.clone())).unwrap();
+    let mut events = Events::with_capacity(1);
+    let err = replay.poll(&mut events).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(events.is_empty());
+    // The batch is skipped.
+    assert_eq!(replay.poll(&mut events).unwrap(), None);
+
+    let mut replay = Replay::new(Cursor::new(recording)).unwrap();
+    let mut events = Events::with_capacity(2);
+    assert_eq!(
+        replay.poll(&mut events).unwrap(),
+        Some(Duration::from_millis(0))
+    );
+    let tokens: Vec<Token> = events.iter().map(|event| event.token()).collect();
+    assert_eq!(tokens, vec![ID1, ID2]);
+    assert!(events.iter().all(|event| event.is_readable()));
+}
+
+#[test]
+fn replay_events_capacity_system_events() {
+    let mut recording = b"mio-rec\x01".to_vec();
+    recording.extend_from_slice(&0u64.to_le_bytes());
+    recording.extend_from_slice(&2u32.to_le_bytes());
+    recording.extend_from_slice(&(ID1.0 as u64).to_le_bytes());
+    recording.push(1 | 2); // Readable and writable.
+    recording.extend_from_slice(&(ID2.0 as u64).to_le_bytes());
+    recording.push(4); // Error only.
+
+    // With kqueue readable and writable readiness are separate events.
+    let capacity = if cfg!(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )) {
+        3
+    } else {
+        2
+    };
+
+    let mut replay = Replay::new(Cursor::new(recording.clone())).unwrap();
+    let mut events = Events::with_capacity(capacity - 1);
+    let err = replay.poll(&mut events).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let mut replay = Replay::new(Cursor::new(recording)).unwrap();
+    let mut events = Events::with_capacity(capacity);
+    replay.poll(&mut events).unwrap().unwrap();
+    assert_eq!(events.iter().count(), capacity);
+    let mut events = events.iter();
+    assert!(events.any(|event| event.token() == ID1 && event.is_readable()));
+    let error = events.last().unwrap();
+    assert_eq!(error.token(), ID2);
+    assert!(error.is_error());
+    assert!(!error.is_readable());
+    assert!(!error.is_writable());
+}
@@ -1,6 +1,7 @@
 #![cfg(all(feature = "os-poll", feature = "net"))]
 
 use std::io::{self, Write};
+use std::net;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -8,12 +9,12 @@ use log::{debug, info, trace};
 #[cfg(debug_assertions)]
 use mio::net::UdpSocket;
 use mio::net::{TcpListener, TcpStream};
-use mio::{Events, Interest, Poll, Registry, Token};
+use mio::{event, Events, Interest, Poll, Registry, Token};
 
 mod util;
 #[cfg(debug_assertions)]
 use util::assert_error;
-use util::{any_local_address, init};
+use util::{any_local_address, expect_events, expect_no_events, init, ExpectEvent};
 
 const SERVER: Token = Token(0);
 const CLIENT: Token = Token(1);
@@ -243,3 +244,290 @@ fn registering_after_deregistering() {
         .unwrap();
     assert!(events.is_empty());
 }
+
+#[test]
+fn register_batch() {
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let mut server = TcpListener::bind(any_local_address()).unwrap();
+    let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+
+    poll.registry()
+        .register_batch(&mut [
+            (&mut server, SERVER, Interest::READABLE),
+            (&mut client, CLIENT, Interest::WRITABLE),
+        ])
+        .unwrap();
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![
+            ExpectEvent::new(SERVER, Interest::READABLE),
+            ExpectEvent::new(CLIENT, Interest::WRITABLE),
+        ],
+    );
+}
+
+#[test]
+fn register_batch_rolls_back() {
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let mut server = TcpListener::bind(any_local_address()).unwrap();
+    let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    let mut failing = FailingSource;
+
+    let err = poll
+        .registry()
+        .register_batch(&mut [
+            (&mut server, SERVER, Interest::READABLE),
+            (&mut failing, Token(2), Interest::READABLE),
+            (&mut client, CLIENT, Interest::WRITABLE),
+        ])
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+
+    // The listener should be deregistered, so no events should be returned
+    // for it, even though a connection is pending.
+    expect_no_events(&mut poll, &mut events);
+
+    // Both sources should be usable again.
+    poll.registry()
+        .register_batch(&mut [
+            (&mut server, SERVER, Interest::READABLE),
+            (&mut client, CLIENT, Interest::WRITABLE),
+        ])
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![
+            ExpectEvent::new(SERVER, Interest::READABLE),
+            ExpectEvent::new(CLIENT, Interest::WRITABLE),
+        ],
+    );
+}
+
+#[test]
+#[cfg(all(unix, feature = "os-ext"))]
+fn register_batch_rolls_back_invalid_fd() {
+    use mio::unix::SourceFd;
+
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let mut server = TcpListener::bind(any_local_address()).unwrap();
+    let _client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+
+    // With kqueue the error is only returned once the registration of both
+    // sources is submitted, after which the listener must be deregistered
+    // again.
+    let err = poll
+        .registry()
+        .register_batch(&mut [
+            (&mut server, SERVER, Interest::READABLE),
+            (&mut SourceFd(&-1), CLIENT, Interest::READABLE),
+        ])
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+    expect_no_events(&mut poll, &mut events);
+
+    poll.registry()
+        .register(&mut server, SERVER, Interest::READABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(SERVER, Interest::READABLE)],
+    );
+}
+
+/// Source that always fails to register.
+struct FailingSource;
+
+impl event::Source for FailingSource {
+    fn register(&mut self, _: &Registry, _: Token, _: Interest) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "registration failed"))
+    }
+
+    fn reregister(&mut self, _: &Registry, _: Token, _: Interest) -> io::Result<()> {
+        unreachable!()
+    }
+
+    fn deregister(&mut self, _: &Registry) -> io::Result<()> {
+        unreachable!()
+    }
+}
+
+#[test]
+fn register_owned() {
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = poll
+        .registry()
+        .register_owned(stream, CLIENT, Interest::READABLE)
+        .unwrap();
+    assert_eq!(stream.token(), CLIENT);
+    assert_eq!(stream.interests(), Interest::READABLE);
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+
+    stream.reregister(Interest::WRITABLE).unwrap();
+    assert_eq!(stream.interests(), Interest::WRITABLE);
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+
+    // Getting the source back deregisters it, so it can be registered again.
+    let mut stream = stream.into_inner().unwrap();
+    poll.registry()
+        .register(&mut stream, CLIENT, Interest::WRITABLE)
+        .unwrap();
+}
+
+/// Source that borrows another source, so the borrowed source is kept open
+/// after it's dropped.
+struct BorrowedSource<'a, S>(&'a mut S);
+
+impl<'a, S: event::Source> event::Source for BorrowedSource<'a, S> {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.0.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.0.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.0.deregister(registry)
+    }
+}
+
+#[test]
+fn register_owned_deregisters_on_drop() {
+    init();
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let registered = poll
+        .registry()
+        .register_owned(BorrowedSource(&mut stream), CLIENT, Interest::WRITABLE)
+        .unwrap();
+    drop(registered);
+
+    // The stream is still open, so we would get an event if it was still
+    // registered.
+    expect_no_events(&mut poll, &mut events);
+
+    poll.registry()
+        .register(&mut stream, CLIENT, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::WRITABLE)],
+    );
+}
+
+#[test]
+fn register_owned_outlives_poll() {
+    init();
+
+    let poll = Poll::new().unwrap();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let stream = poll
+        .registry()
+        .register_owned(stream, CLIENT, Interest::WRITABLE)
+        .unwrap();
+    drop(poll);
+
+    // The selector is kept alive by the guard, so this still succeeds.
+    stream.into_inner().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn probe() {
+    let (mut poll, mut events) = util::init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, CLIENT, Interest::READABLE)
+        .unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let readiness = poll.registry().probe(&stream, Interest::READABLE).unwrap();
+    assert!(readiness.is_empty());
+    let readiness = poll
+        .registry()
+        .probe(&stream, Interest::READABLE | Interest::WRITABLE)
+        .unwrap();
+    assert!(!readiness.is_readable());
+    assert!(readiness.is_writable());
+
+    peer.write_all(b"hello world").unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(CLIENT, Interest::READABLE)],
+    );
+
+    // Partially drain the stream, no new event is returned but the stream is
+    // still readable.
+    let mut buf = [0; 5];
+    assert_eq!(io::Read::read(&mut stream, &mut buf).unwrap(), 5);
+    expect_no_events(&mut poll, &mut events);
+    let readiness = poll.registry().probe(&stream, Interest::READABLE).unwrap();
+    assert!(readiness.is_readable());
+    assert!(!readiness.is_read_closed());
+
+    // Probing doesn't consume the readiness.
+    let readiness = poll.registry().probe(&stream, Interest::READABLE).unwrap();
+    assert!(readiness.is_readable());
+
+    let mut buf = [0; 16];
+    assert_eq!(io::Read::read(&mut stream, &mut buf).unwrap(), 6);
+    let readiness = poll.registry().probe(&stream, Interest::READABLE).unwrap();
+    assert!(!readiness.is_readable());
+
+    drop(peer);
+    sleep(Duration::from_millis(100));
+    let readiness = poll.registry().probe(&stream, Interest::READABLE).unwrap();
+    assert!(readiness.is_readable());
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert!(readiness.is_read_closed());
+}
@@ -1,6 +1,6 @@
 #![cfg(all(feature = "os-poll", feature = "net"))]
 
-use mio::net::TcpListener;
+use mio::net::{TcpKeepalive, TcpListener, TcpSocket};
 use mio::{Interest, Token};
 use std::io::{self, Read};
 use std::net::{self, SocketAddr};
@@ -8,8 +8,11 @@ use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::sync::{Arc, Barrier};
 use std::thread;
+use std::time::Duration;
 
 mod util;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use util::assert_bind_device_and_mark;
 use util::{
     any_local_address, any_local_ipv6_address, assert_send, assert_socket_close_on_exec,
     assert_socket_non_blocking, assert_sync, assert_would_block, expect_events, expect_no_events,
@@ -282,3 +285,71 @@ fn start_connections(
         }
     })
 }
+
+#[test]
+fn socket_options() {
+    let listener = TcpListener::bind(any_local_address()).unwrap();
+
+    listener.set_linger(Some(Duration::from_secs(1))).unwrap();
+    assert_eq!(listener.linger().unwrap(), Some(Duration::from_secs(1)));
+    listener.set_linger(None).unwrap();
+    assert_eq!(listener.linger().unwrap(), None);
+
+    listener.set_recv_buffer_size(16 * 1024).unwrap();
+    assert!(listener.recv_buffer_size().unwrap() >= 16 * 1024);
+    listener.set_send_buffer_size(16 * 1024).unwrap();
+    assert!(listener.send_buffer_size().unwrap() >= 16 * 1024);
+
+    assert!(!listener.keepalive().unwrap());
+    listener
+        .set_keepalive_params(TcpKeepalive::default().with_time(Duration::from_secs(4)))
+        .unwrap();
+    assert!(listener.keepalive().unwrap());
+    #[cfg(not(target_os = "windows"))]
+    assert_eq!(
+        listener.keepalive_time().unwrap(),
+        Some(Duration::from_secs(4))
+    );
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn bind_device_and_mark() {
+    assert_bind_device_and_mark(|| TcpListener::bind(any_local_address()).unwrap());
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+#[test]
+fn tcp_info() {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(any_local_address()).unwrap();
+    let listener = socket.listen(16).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let info = listener.tcp_info().unwrap();
+    assert!(info.rtt().is_none());
+    assert!(info.cwnd().is_none());
+    if cfg!(any(target_os = "ios", target_os = "macos")) {
+        // The accept queue isn't reported.
+        assert!(info.accept_queue_len().is_none());
+        assert!(info.accept_queue_max().is_none());
+        return;
+    }
+    assert_eq!(info.accept_queue_len(), Some(0));
+    assert_eq!(info.accept_queue_max(), Some(16));
+
+    // Connections are established by the kernel, without accepting them.
+    let _streams: Vec<_> = (0..2)
+        .map(|_| net::TcpStream::connect(addr).unwrap())
+        .collect();
+    assert_eq!(listener.tcp_info().unwrap().accept_queue_len(), Some(2));
+
+    listener.accept().unwrap();
+    assert_eq!(listener.tcp_info().unwrap().accept_queue_len(), Some(1));
+}
@@ -2,8 +2,18 @@
 
 use mio::net::{TcpKeepalive, TcpSocket};
 use std::io;
+use std::net::{self, IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 
+mod util;
+use util::assert_would_block;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use util::{
+    allowed_congestion_control, any_local_address, assert_bind_device_and_mark,
+    available_congestion_control, expect_events, init_with_poll, without_capabilities, ExpectEvent,
+    CAP_NET_ADMIN,
+};
+
 #[test]
 fn is_send_and_sync() {
     fn is_send<T: Send>() {}
@@ -123,6 +133,218 @@ fn set_keepalive_retries() {
     let _ = socket.listen(128).unwrap();
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_busy_poll() {
+    let socket = TcpSocket::new_v4().unwrap();
+
+    // Increasing the value requires `CAP_NET_ADMIN`.
+    match socket.set_busy_poll(Duration::from_micros(50)) {
+        Ok(()) => assert_eq!(socket.get_busy_poll().unwrap(), Duration::from_micros(50)),
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::PermissionDenied),
+    }
+
+    socket.set_busy_poll(Duration::from_micros(0)).unwrap();
+    assert_eq!(socket.get_busy_poll().unwrap(), Duration::from_micros(0));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_prefer_busy_poll() {
+    let socket = TcpSocket::new_v4().unwrap();
+
+    match socket.set_prefer_busy_poll(true) {
+        Ok(()) => {
+            assert!(socket.get_prefer_busy_poll().unwrap());
+            socket.set_prefer_busy_poll(false).unwrap();
+            assert!(!socket.get_prefer_busy_poll().unwrap());
+        }
+        // Not supported before Linux 5.11.
+        Err(err) => assert_eq!(err.raw_os_error(), Some(libc::ENOPROTOOPT)),
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_fastopen() {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_fastopen(16).unwrap();
+    assert_eq!(socket.get_fastopen().unwrap(), 16);
+    socket.set_fastopen(0).unwrap();
+    assert_eq!(socket.get_fastopen().unwrap(), 0);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_user_timeout() {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket
+        .set_user_timeout(Duration::from_millis(1500))
+        .unwrap();
+    assert_eq!(
+        socket.get_user_timeout().unwrap(),
+        Duration::from_millis(1500)
+    );
+    socket.set_user_timeout(Duration::from_secs(0)).unwrap();
+    assert_eq!(socket.get_user_timeout().unwrap(), Duration::from_secs(0));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_notsent_lowat() {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_notsent_lowat(16 * 1024).unwrap();
+    assert_eq!(socket.get_notsent_lowat().unwrap(), 16 * 1024);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn bind_device_and_mark() {
+    assert_bind_device_and_mark(|| TcpSocket::new_v4().unwrap());
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_congestion_control() {
+    let socket = TcpSocket::new_v4().unwrap();
+    let allowed = allowed_congestion_control();
+    for name in &allowed {
+        socket.set_congestion_control(name).unwrap();
+        assert_eq!(&socket.get_congestion_control().unwrap(), name);
+    }
+
+    // Algorithms that are available, but not allowed, require
+    // `CAP_NET_ADMIN`.
+    without_capabilities(&[CAP_NET_ADMIN], move || {
+        let socket = TcpSocket::new_v4().unwrap();
+        for name in available_congestion_control() {
+            if !allowed.contains(&name) {
+                let err = socket.set_congestion_control(&name).unwrap_err();
+                assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+            }
+        }
+    });
+
+    let err = socket
+        .set_congestion_control("not-an-algorithm")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("not-an-algorithm"));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_bind_address_no_port() {
+    let socket = TcpSocket::new_v4().unwrap();
+    assert!(!socket.get_bind_address_no_port().unwrap());
+    socket.set_bind_address_no_port(true).unwrap();
+    assert!(socket.get_bind_address_no_port().unwrap());
+
+    // The port is selected when connecting.
+    socket.bind(any_local_address()).unwrap();
+    assert_eq!(socket.get_localaddr().unwrap().port(), 0);
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = socket.connect(listener.local_addr().unwrap()).unwrap();
+    assert_ne!(stream.local_addr().unwrap().port(), 0);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn set_local_port_range() {
+    let socket = TcpSocket::new_v4().unwrap();
+    match socket.set_local_port_range(0, 0) {
+        Ok(()) => {}
+        // Not supported before Linux 6.3.
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOPROTOOPT) => return,
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+    assert_eq!(socket.get_local_port_range().unwrap(), (0, 0));
+
+    // Limit the range to a single port, connections to different remote
+    // addresses can still share it.
+    let (_, port) = system_local_port_range();
+    for _ in 0..2 {
+        let listener = net::TcpListener::bind(any_local_address()).unwrap();
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_bind_address_no_port(true).unwrap();
+        socket.set_local_port_range(port, port).unwrap();
+        assert_eq!(socket.get_local_port_range().unwrap(), (port, port));
+        socket.bind(any_local_address()).unwrap();
+        let stream = socket.connect(listener.local_addr().unwrap()).unwrap();
+        assert_eq!(stream.local_addr().unwrap().port(), port);
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn connect_fastopen() {
+    use mio::{Interest, Token};
+    use std::io::{Read, Write};
+
+    let (mut poll, mut events) = init_with_poll();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(any_local_address()).unwrap();
+    socket.set_fastopen(16).unwrap();
+    let listener = socket.listen(16).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // The first connection gets the cookie (if enabled), the second can use
+    // it to send data with the SYN.
+    for _ in 0..2 {
+        let mut stream = TcpSocket::new_v4().unwrap().connect_fastopen(addr).unwrap();
+        poll.registry()
+            .register(&mut stream, Token(0), Interest::WRITABLE)
+            .unwrap();
+        expect_events(
+            &mut poll,
+            &mut events,
+            vec![ExpectEvent::new(Token(0), Interest::WRITABLE)],
+        );
+        assert_eq!(stream.write(b"hello").unwrap(), 5);
+
+        let (mut peer, _) = retry_would_block(|| listener.accept());
+        let mut buf = [0; 16];
+        let n = retry_would_block(|| peer.read(&mut buf));
+        assert_eq!(&buf[..n], b"hello");
+    }
+}
+
+#[test]
+fn set_only_v6() {
+    let unspecified: SocketAddr = "[::]:0".parse().unwrap();
+
+    // Dual-stack: the listener accepts IPv4 connections.
+    let socket = TcpSocket::new_v6().unwrap();
+    socket.set_only_v6(false).unwrap();
+    assert!(!socket.get_only_v6().unwrap());
+    socket.bind(unspecified).unwrap();
+    let listener = socket.listen(128).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let stream = net::TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+    let (_, peer_addr) = retry_would_block(|| listener.accept());
+    assert_eq!(peer_addr.port(), stream.local_addr().unwrap().port());
+    match peer_addr.ip() {
+        IpAddr::V6(ip) => assert_eq!(ip.to_ipv4(), Some(Ipv4Addr::LOCALHOST)),
+        IpAddr::V4(ip) => panic!("unexpected IPv4 address: {}", ip),
+    }
+
+    // IPv6 only: the port is free for IPv4 sockets.
+    let socket = TcpSocket::new_v6().unwrap();
+    socket.set_only_v6(true).unwrap();
+    assert!(socket.get_only_v6().unwrap());
+    socket.bind(unspecified).unwrap();
+    let listener = socket.listen(128).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let v4_listener = net::TcpListener::bind((Ipv4Addr::LOCALHOST, port)).unwrap();
+    let stream = net::TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+    assert_eq!(
+        v4_listener.accept().unwrap().1,
+        stream.local_addr().unwrap()
+    );
+    assert_would_block(listener.accept());
+}
+
 #[test]
 fn get_localaddr() {
     let expected_addr = "127.0.0.1:0".parse().unwrap();
@@ -201,3 +423,27 @@ fn test_buffer_sizes(
     test(4096);
     test(65512);
 }
+
+/// Retry `f` until it doesn't return a `WouldBlock` error.
+fn retry_would_block<T, F>(mut f: F) -> T
+where
+    F: FnMut() -> io::Result<T>,
+{
+    loop {
+        match f() {
+            Ok(value) => return value,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(10))
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+}
+
+/// Returns the `net.ipv4.ip_local_port_range` sysctl.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn system_local_port_range() -> (u16, u16) {
+    let range = std::fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range").unwrap();
+    let mut ports = range.split_whitespace().map(|port| port.parse().unwrap());
+    (ports.next().unwrap(), ports.next().unwrap())
+}
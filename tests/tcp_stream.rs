@@ -11,11 +11,13 @@ use std::sync::{mpsc::channel, Arc, Barrier};
 use std::thread;
 use std::time::Duration;
 
-use mio::net::{TcpSocket, TcpStream};
+use mio::net::{TcpKeepalive, TcpSocket, TcpStream};
 use mio::{Interest, Token};
 
 #[macro_use]
 mod util;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use util::allowed_congestion_control;
 #[cfg(not(target_os = "windows"))]
 use util::init;
 use util::{
@@ -810,3 +812,205 @@ fn set_linger_zero(socket: &TcpStream) {
     s.set_linger(Some(Duration::from_millis(0))).unwrap();
     forget(s);
 }
+
+#[test]
+fn socket_options() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    // Accepted streams can be configured as well.
+    let (peer, _) = listener.accept().unwrap();
+    let peer = TcpStream::from_std(peer);
+
+    for stream in [&stream, &peer].iter() {
+        stream.set_linger(Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(1)));
+        stream.set_linger(None).unwrap();
+        assert_eq!(stream.linger().unwrap(), None);
+
+        stream.set_recv_buffer_size(16 * 1024).unwrap();
+        assert!(stream.recv_buffer_size().unwrap() >= 16 * 1024);
+        stream.set_send_buffer_size(16 * 1024).unwrap();
+        assert!(stream.send_buffer_size().unwrap() >= 16 * 1024);
+
+        stream.set_keepalive(false).unwrap();
+        assert!(!stream.keepalive().unwrap());
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(stream.keepalive_time().unwrap(), None);
+
+        let keepalive = TcpKeepalive::default().with_time(Duration::from_secs(4));
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+        let keepalive = keepalive
+            .with_interval(Duration::from_secs(2))
+            .with_retries(3);
+        stream.set_keepalive_params(keepalive).unwrap();
+        assert!(stream.keepalive().unwrap());
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(
+            stream.keepalive_time().unwrap(),
+            Some(Duration::from_secs(4))
+        );
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+        ))]
+        {
+            assert_eq!(
+                stream.keepalive_interval().unwrap(),
+                Some(Duration::from_secs(2))
+            );
+            assert_eq!(stream.keepalive_retries().unwrap(), Some(3));
+        }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            stream.set_user_timeout(Duration::from_secs(10)).unwrap();
+            assert_eq!(stream.user_timeout().unwrap(), Duration::from_secs(10));
+            stream.set_notsent_lowat(16 * 1024).unwrap();
+            assert_eq!(stream.notsent_lowat().unwrap(), 16 * 1024);
+
+            for name in allowed_congestion_control() {
+                stream.set_congestion_control(&name).unwrap();
+                assert_eq!(stream.congestion_control().unwrap(), name);
+            }
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+#[test]
+fn tcp_info() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (mut peer, _) = listener.accept().unwrap();
+    checked_write!(stream.write(DATA1));
+    let mut buf = [0; 16];
+    assert_eq!(peer.read(&mut buf).unwrap(), DATA1.len());
+
+    let info = stream.tcp_info().unwrap();
+    assert!(info.rtt().is_some());
+    assert!(info.rtt_var().is_some());
+    assert!(info.cwnd().unwrap() > 0);
+    assert_eq!(info.retransmits(), Some(0));
+    if cfg!(any(target_os = "android", target_os = "linux")) {
+        assert!(info.unacked().is_some());
+    } else {
+        assert!(info.unacked().is_none());
+    }
+    assert!(info.accept_queue_len().is_none());
+    assert!(info.accept_queue_max().is_none());
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn mptcp() {
+    use mio::net::{TcpListener, TcpSocket};
+
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = match TcpListener::bind_mptcp(any_local_address()) {
+        Ok(listener) => listener,
+        // MPTCP isn't supported (`EPROTONOSUPPORT`) or disabled (`ENOPROTOOPT`).
+        Err(ref err)
+            if err.raw_os_error() == Some(libc::EPROTONOSUPPORT)
+                || err.raw_os_error() == Some(libc::ENOPROTOOPT) =>
+        {
+            return;
+        }
+        Err(err) => panic!("unexpected error: {}", err),
+    };
+    let addr = listener.local_addr().unwrap();
+
+    // MPTCP on both sides.
+    let mut stream = TcpSocket::new_mptcp_v4().unwrap().connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, ID1, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::WRITABLE)],
+    );
+    let (peer, _) = listener.accept().unwrap();
+    assert!(stream.is_mptcp().unwrap());
+    assert!(peer.is_mptcp().unwrap());
+
+    // A plain TCP client, the connection falls back to TCP.
+    let mut stream = TcpStream::connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, ID2, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::WRITABLE)],
+    );
+    let (peer, _) = listener.accept().unwrap();
+    assert!(!stream.is_mptcp().unwrap());
+    assert!(!peer.is_mptcp().unwrap());
+}
+
+#[test]
+fn connect_from() {
+    const CONNECTIONS: usize = 64;
+
+    let local = "127.0.0.1:0".parse().unwrap();
+    let listeners: Vec<_> = (0..2)
+        .map(|_| net::TcpListener::bind(any_local_address()).unwrap())
+        .collect();
+
+    let mut streams = Vec::new();
+    for listener in listeners.iter() {
+        let remote = listener.local_addr().unwrap();
+        for _ in 0..CONNECTIONS {
+            let stream = TcpStream::connect_from(local, remote).unwrap();
+            assert_eq!(stream.local_addr().unwrap().ip(), local.ip());
+            streams.push(stream);
+        }
+    }
+
+    // All connections are established.
+    for listener in listeners.iter() {
+        for _ in 0..CONNECTIONS {
+            let (_, peer_addr) = listener.accept().unwrap();
+            assert_eq!(peer_addr.ip(), local.ip());
+        }
+    }
+    for stream in streams.iter() {
+        assert!(stream.take_error().unwrap().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+#![cfg(all(feature = "os-poll", feature = "net"))]
+
+use std::io::Write;
+use std::net;
+use std::time::Duration;
+
+use mio::net::TcpStream;
+use mio::{Interest, Token, TokenMap};
+
+mod util;
+use util::{any_local_address, assert_send, assert_sync, init_with_poll};
+
+#[test]
+fn is_send_and_sync() {
+    assert_send::<TokenMap<TcpStream>>();
+    assert_sync::<TokenMap<TcpStream>>();
+}
+
+#[test]
+fn insert_get_remove() {
+    let mut map = TokenMap::new();
+    assert!(map.is_empty());
+
+    let t1 = map.insert("a");
+    let t2 = map.insert("b");
+    assert_ne!(t1, t2);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(t1), Some(&"a"));
+    assert_eq!(map.get(t2), Some(&"b"));
+
+    *map.get_mut(t2).unwrap() = "c";
+    assert_eq!(map.get(t2), Some(&"c"));
+
+    assert_eq!(map.remove(t1), Some("a"));
+    assert_eq!(map.remove(t1), None);
+    assert!(!map.contains(t1));
+    assert_eq!(map.len(), 1);
+
+    let tokens: Vec<Token> = map.iter().map(|(token, _)| token).collect();
+    assert_eq!(tokens, vec![t2]);
+}
+
+#[test]
+fn next_token() {
+    let mut map = TokenMap::new();
+
+    let token = map.next_token();
+    assert_eq!(map.insert(1), token);
+    let token = map.next_token();
+    assert_eq!(map.insert(2), token);
+
+    map.remove(token);
+    let token = map.next_token();
+    assert_eq!(map.insert(3), token);
+}
+
+#[test]
+fn reused_slots_reject_stale_tokens() {
+    let mut map = TokenMap::new();
+
+    let old = map.insert(1);
+    map.remove(old);
+    let new = map.insert(2);
+
+    // Same slot, but different generation.
+    assert_ne!(old, new);
+    assert_eq!(map.get(old), None);
+    assert_eq!(map.get_mut(old), None);
+    assert_eq!(map.remove(old), None);
+    assert_eq!(map.get(new), Some(&2));
+}
+
+#[test]
+fn unknown_tokens() {
+    let mut map = TokenMap::new();
+    map.insert(1);
+
+    // Tokens not allocated by the map never resolve.
+    for token in [Token(0), Token(1), Token(usize::MAX)].iter() {
+        assert_eq!(map.get(*token), None);
+    }
+}
+
+#[test]
+fn stale_events_are_rejected() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut map = TokenMap::new();
+    let token = map.next_token();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, token, Interest::WRITABLE)
+        .unwrap();
+    assert_eq!(map.insert(stream), token);
+
+    let (mut peer, _) = listener.accept().unwrap();
+    peer.write_all(b"hello").unwrap();
+
+    poll.poll(&mut events, Some(Duration::from_millis(500)))
+        .unwrap();
+    assert!(!events.is_empty());
+    for event in events.iter() {
+        assert!(map.resolve_mut(event).is_some());
+    }
+
+    // Remove the connection and reuse its slot for another one, while the
+    // events for the first connection are still around.
+    let mut stream = map.remove(token).unwrap();
+    poll.registry().deregister(&mut stream).unwrap();
+    drop(stream);
+
+    let new_token = map.next_token();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    poll.registry()
+        .register(&mut stream, new_token, Interest::WRITABLE)
+        .unwrap();
+    map.insert(stream);
+
+    for event in events.iter() {
+        assert!(map.resolve(event).is_none());
+        assert!(map.resolve_mut(event).is_none());
+    }
+}
@@ -1,9 +1,10 @@
 #![cfg(all(feature = "os-poll", feature = "net"))]
 
 use log::{debug, info};
-use mio::net::UdpSocket;
+use mio::net::{RecvMeta, UdpSocket, UdpSocketBuilder};
 use mio::{Events, Interest, Poll, Registry, Token};
-use std::net::{self, IpAddr, SocketAddr};
+use std::io;
+use std::net::{self, IpAddr, Ipv4Addr, SocketAddr};
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::str;
@@ -13,6 +14,8 @@ use std::time::Duration;
 
 #[macro_use]
 mod util;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use util::assert_bind_device_and_mark;
 use util::{
     any_local_address, any_local_ipv6_address, assert_error, assert_send,
     assert_socket_close_on_exec, assert_socket_non_blocking, assert_sync, assert_would_block,
@@ -640,6 +643,435 @@ fn connected_udp_socket_unconnected_methods() {
     assert!(socket3.take_error().unwrap().is_none());
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_busy_poll() {
+    init();
+
+    let socket = UdpSocket::bind(any_local_address()).unwrap();
+
+    // Increasing the value requires `CAP_NET_ADMIN`.
+    match socket.set_busy_poll(Duration::from_micros(50)) {
+        Ok(()) => assert_eq!(socket.busy_poll().unwrap(), Duration::from_micros(50)),
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::PermissionDenied),
+    }
+    socket.set_busy_poll(Duration::from_micros(0)).unwrap();
+    assert_eq!(socket.busy_poll().unwrap(), Duration::from_micros(0));
+
+    match socket.set_prefer_busy_poll(true) {
+        Ok(()) => assert!(socket.prefer_busy_poll().unwrap()),
+        // Not supported before Linux 5.11.
+        Err(err) => assert_eq!(err.raw_os_error(), Some(libc::ENOPROTOOPT)),
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_bind_device_and_mark() {
+    init();
+
+    assert_bind_device_and_mark(|| UdpSocket::bind(any_local_address()).unwrap());
+}
+
+#[test]
+fn udp_socket_send_recv_many() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    let address1 = socket1.local_addr().unwrap();
+    let address2 = socket2.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    let mut bufs = [[0; 16]; 4];
+    let mut meta = [RecvMeta::new(); 4];
+    assert_would_block(socket1.recv_many(&mut bufs, &mut meta));
+
+    let err = socket2.send_many(&[DATA1, DATA2], &[address1]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let sent = socket2
+        .send_many(&[DATA1, DATA2], &[address1, address1])
+        .unwrap();
+    assert_eq!(sent, 2);
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    assert_eq!(socket1.recv_many(&mut bufs, &mut meta).unwrap(), 2);
+    for (i, expected) in [DATA1, DATA2].iter().enumerate() {
+        assert_eq!(&bufs[i][..meta[i].len()], *expected);
+        assert_eq!(meta[i].addr(), address2);
+        assert!(!meta[i].is_truncated());
+    }
+
+    assert_would_block(socket1.recv_many(&mut bufs, &mut meta));
+}
+
+#[test]
+fn udp_socket_send_recv_many_large_batch() {
+    const N: usize = 50;
+
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    let address1 = socket1.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    // More datagrams than are sent or received in a single system call.
+    let data: Vec<[u8; 1]> = (0..N as u8).map(|i| [i]).collect();
+    let addrs = vec![address1; N];
+    let mut sent = 0;
+    while sent < N {
+        sent += socket2.send_many(&data[sent..], &addrs[sent..]).unwrap();
+    }
+
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let mut bufs = vec![[0; 16]; N + 1];
+    let mut meta = vec![RecvMeta::new(); N + 1];
+    let mut received = 0;
+    while received < N {
+        let n = socket1.recv_many(&mut bufs, &mut meta).unwrap();
+        for (buf, meta) in bufs.iter().zip(meta.iter()).take(n) {
+            assert_eq!(&buf[..meta.len()], &[received as u8]);
+            received += 1;
+        }
+    }
+    assert_would_block(socket1.recv_many(&mut bufs, &mut meta));
+}
+
+// On Windows truncated datagrams return an error.
+#[cfg(unix)]
+#[test]
+fn udp_socket_recv_many_truncated() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    let address1 = socket1.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    checked_write!(socket2.send_to(DATA1, address1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let mut bufs = [[0; 5]; 2];
+    let mut meta = [RecvMeta::new(); 2];
+    assert_eq!(socket1.recv_many(&mut bufs, &mut meta).unwrap(), 1);
+    assert_eq!(&bufs[0][..meta[0].len()], &DATA1[..5]);
+    assert!(meta[0].is_truncated());
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_send_segmented() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    if !socket2.supports_gso().unwrap() {
+        info!("skipping test, UDP_SEGMENT not supported");
+        return;
+    }
+    let address1 = socket1.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    let data = segmented_data();
+    checked_write!(socket2.send_segmented(&data, 100, address1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+
+    // Without GRO the datagrams are received separately.
+    let mut bufs = [[0; 200]; 8];
+    let mut meta = [RecvMeta::new(); 8];
+    assert_eq!(socket1.recv_many(&mut bufs, &mut meta).unwrap(), 4);
+    for (i, (buf, meta)) in bufs.iter().zip(meta.iter()).take(4).enumerate() {
+        assert_eq!(meta.segment_size(), None);
+        assert_eq!(meta.addr(), socket2.local_addr().unwrap());
+        assert_eq!(&buf[..meta.len()], data.chunks(100).nth(i).unwrap());
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_gro() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    if !socket2.supports_gso().unwrap() {
+        info!("skipping test, UDP_SEGMENT not supported");
+        return;
+    }
+    match socket1.set_gro(true) {
+        Ok(()) => {}
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOPROTOOPT) => {
+            info!("skipping test, UDP_GRO not supported");
+            return;
+        }
+        Err(err) => panic!("unexpected error setting UDP_GRO: {}", err),
+    }
+    assert!(socket1.gro().unwrap());
+    let address1 = socket1.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+
+    let data = segmented_data();
+    checked_write!(socket2.send_segmented(&data, 100, address1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+
+    // On loopback the datagrams are coalesced into a single buffer.
+    let mut buf = vec![0; 64 * 1024];
+    let meta = socket1.recv_msg(&mut buf).unwrap();
+    assert!(!meta.is_truncated());
+    assert_eq!(meta.addr(), socket2.local_addr().unwrap());
+    assert_eq!(meta.len(), data.len());
+    assert_eq!(meta.segment_size(), Some(100));
+    assert_eq!(&buf[..meta.len()], &*data);
+    assert_would_block(socket1.recv_msg(&mut buf));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_pktinfo_v4() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind("0.0.0.0:0".parse().unwrap()).unwrap();
+    assert!(!socket1.recv_pktinfo().unwrap());
+    socket1.set_recv_pktinfo(true).unwrap();
+    assert!(socket1.recv_pktinfo().unwrap());
+    let mut socket2 = UdpSocket::bind(any_local_address()).unwrap();
+    // All of 127.0.0.0/8 is local, so a reply without a source address set
+    // would come from 127.0.0.1.
+    let dst_addr: IpAddr = "127.0.0.2".parse().unwrap();
+    let address1 = SocketAddr::new(dst_addr, socket1.local_addr().unwrap().port());
+    let address2 = socket2.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut socket2, ID2, Interest::READABLE)
+        .unwrap();
+
+    checked_write!(socket2.send_to(DATA1, address1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let mut buf = [0; 20];
+    let meta = socket1.recv_msg(&mut buf).unwrap();
+    assert_eq!(&buf[..meta.len()], DATA1);
+    assert_eq!(meta.addr(), address2);
+    assert_eq!(meta.dst_addr(), Some(dst_addr));
+    assert_eq!(meta.interface_index(), Some(loopback_index()));
+
+    checked_write!(socket1.send_msg(DATA2, meta.addr(), dst_addr));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::READABLE)],
+    );
+    expect_read!(socket2.recv_from(&mut buf), DATA2, address1);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn udp_socket_pktinfo_v6() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let mut socket1 = UdpSocket::bind("[::]:0".parse().unwrap()).unwrap();
+    socket1.set_recv_pktinfo(true).unwrap();
+    assert!(socket1.recv_pktinfo().unwrap());
+    let mut socket2 = UdpSocket::bind(any_local_ipv6_address()).unwrap();
+    let dst_addr: IpAddr = "::1".parse().unwrap();
+    let address1 = SocketAddr::new(dst_addr, socket1.local_addr().unwrap().port());
+    let address2 = socket2.local_addr().unwrap();
+    poll.registry()
+        .register(&mut socket1, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut socket2, ID2, Interest::READABLE)
+        .unwrap();
+
+    checked_write!(socket2.send_to(DATA1, address1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    // Packet info is also reported by `recv_many`.
+    let mut bufs = [[0; 20]; 2];
+    let mut meta = [RecvMeta::new(); 2];
+    assert_eq!(socket1.recv_many(&mut bufs, &mut meta).unwrap(), 1);
+    assert_eq!(&bufs[0][..meta[0].len()], DATA1);
+    assert_eq!(meta[0].addr(), address2);
+    assert_eq!(meta[0].dst_addr(), Some(dst_addr));
+    assert_eq!(meta[0].interface_index(), Some(loopback_index()));
+
+    checked_write!(socket1.send_msg(DATA2, meta[0].addr(), dst_addr));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::READABLE)],
+    );
+    let mut buf = [0; 20];
+    expect_read!(socket2.recv_from(&mut buf), DATA2, address1);
+}
+
+/// Returns the index of the loopback interface.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn loopback_index() -> u32 {
+    let index = unsafe { libc::if_nametoindex(b"lo\0".as_ptr() as *const libc::c_char) };
+    assert_ne!(index, 0, "no loopback interface");
+    index
+}
+
+/// Returns 3 segments of 100 bytes and a smaller segment of 50 bytes.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn segmented_data() -> Vec<u8> {
+    (0..350).map(|i| (i / 100) as u8 + b'a').collect()
+}
+
+#[test]
+fn udp_socket_builder() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let builder = UdpSocketBuilder::new_v4().unwrap();
+    builder.set_reuseaddr(true).unwrap();
+    assert!(builder.get_reuseaddr().unwrap());
+    builder.set_recv_buffer_size(16 * 1024).unwrap();
+    assert!(builder.get_recv_buffer_size().unwrap() >= 16 * 1024);
+    builder.set_send_buffer_size(16 * 1024).unwrap();
+    assert!(builder.get_send_buffer_size().unwrap() >= 16 * 1024);
+    builder.set_broadcast(true).unwrap();
+    assert!(builder.get_broadcast().unwrap());
+    let mut socket = builder.bind(any_local_address()).unwrap();
+    assert!(socket.broadcast().unwrap());
+    let address = socket.local_addr().unwrap();
+
+    // The socket is bound by the OS when connecting.
+    let mut sender = UdpSocketBuilder::new_v4()
+        .unwrap()
+        .connect(address)
+        .unwrap();
+    assert_ne!(sender.local_addr().unwrap().port(), 0);
+    assert_eq!(sender.peer_addr().unwrap(), address);
+
+    poll.registry()
+        .register(&mut socket, ID1, Interest::READABLE)
+        .unwrap();
+    poll.registry()
+        .register(&mut sender, ID2, Interest::WRITABLE)
+        .unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID2, Interest::WRITABLE)],
+    );
+    checked_write!(sender.send(DATA1));
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let mut buf = [0; 20];
+    let (n, from) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA1);
+    assert_eq!(from, sender.local_addr().unwrap());
+}
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+#[test]
+fn udp_socket_builder_reuseport() {
+    init();
+
+    let builder = UdpSocketBuilder::new_v4().unwrap();
+    builder.set_reuseport(true).unwrap();
+    assert!(builder.get_reuseport().unwrap());
+    let socket1 = builder.bind(any_local_address()).unwrap();
+    let address = socket1.local_addr().unwrap();
+
+    // Without `SO_REUSEPORT` the address can't be reused.
+    let err = UdpSocketBuilder::new_v4()
+        .unwrap()
+        .bind(address)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+    let builder = UdpSocketBuilder::new_v4().unwrap();
+    builder.set_reuseport(true).unwrap();
+    let socket2 = builder.bind(address).unwrap();
+    assert_eq!(socket2.local_addr().unwrap(), address);
+}
+
+#[test]
+fn udp_socket_builder_only_v6() {
+    let (mut poll, mut events) = init_with_poll();
+
+    let unspecified: SocketAddr = "[::]:0".parse().unwrap();
+
+    // Dual-stack: the socket receives IPv4 datagrams.
+    let builder = UdpSocketBuilder::new_v6().unwrap();
+    builder.set_only_v6(false).unwrap();
+    assert!(!builder.get_only_v6().unwrap());
+    let mut socket = builder.bind(unspecified).unwrap();
+    assert!(!socket.only_v6().unwrap());
+    let port = socket.local_addr().unwrap().port();
+    poll.registry()
+        .register(&mut socket, ID1, Interest::READABLE)
+        .unwrap();
+
+    let sender = net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    sender.send_to(DATA1, (Ipv4Addr::LOCALHOST, port)).unwrap();
+    expect_events(
+        &mut poll,
+        &mut events,
+        vec![ExpectEvent::new(ID1, Interest::READABLE)],
+    );
+    let mut buf = [0; 20];
+    let (n, peer_addr) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA1);
+    assert_eq!(peer_addr.port(), sender.local_addr().unwrap().port());
+    match peer_addr.ip() {
+        IpAddr::V6(ip) => assert_eq!(ip.to_ipv4(), Some(Ipv4Addr::LOCALHOST)),
+        IpAddr::V4(ip) => panic!("unexpected IPv4 address: {}", ip),
+    }
+
+    // IPv6 only: the port is free for IPv4 sockets.
+    let builder = UdpSocketBuilder::new_v6().unwrap();
+    builder.set_only_v6(true).unwrap();
+    assert!(builder.get_only_v6().unwrap());
+    let socket = builder.bind(unspecified).unwrap();
+    assert!(socket.only_v6().unwrap());
+    let port = socket.local_addr().unwrap().port();
+    net::UdpSocket::bind((Ipv4Addr::LOCALHOST, port)).unwrap();
+}
+
 #[cfg(unix)]
 #[test]
 fn udp_socket_raw_fd() {
@@ -8,10 +8,12 @@ use std::ops::BitOr;
 use std::os::unix::io::AsRawFd;
 use std::path::PathBuf;
 use std::sync::Once;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::thread;
 use std::time::Duration;
 use std::{env, fmt, fs, io};
 
-use log::{error, warn};
+use log::{error, info, warn};
 use mio::event::Event;
 use mio::{Events, Interest, Poll, Token};
 
@@ -236,6 +238,181 @@ pub fn any_local_ipv6_address() -> SocketAddr {
     "[::1]:0".parse().unwrap()
 }
 
+/// Returns the available TCP congestion control algorithms.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn available_congestion_control() -> Vec<String> {
+    read_congestion_control("/proc/sys/net/ipv4/tcp_available_congestion_control")
+}
+
+/// Returns the TCP congestion control algorithms that can be set without
+/// `CAP_NET_ADMIN`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn allowed_congestion_control() -> Vec<String> {
+    read_congestion_control("/proc/sys/net/ipv4/tcp_allowed_congestion_control")
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn read_congestion_control(path: &str) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+/// `CAP_NET_ADMIN` capability, see `capabilities(7)`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub const CAP_NET_ADMIN: u32 = 12;
+/// `CAP_NET_RAW` capability, see `capabilities(7)`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub const CAP_NET_RAW: u32 = 13;
+
+/// Returns `true` if the calling thread has the capability `cap` in its
+/// effective set.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn has_capability(cap: u32) -> bool {
+    let (_, data) = get_capabilities();
+    data[(cap / 32) as usize].effective & (1 << (cap % 32)) != 0
+}
+
+/// Runs `f` on a new thread with the capabilities `caps` removed from the
+/// effective set of the thread. Capabilities are per thread, so this doesn't
+/// affect other tests.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn without_capabilities<F, T>(caps: &'static [u32], f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    thread::spawn(move || {
+        let (mut header, mut data) = get_capabilities();
+        for cap in caps {
+            data[(cap / 32) as usize].effective &= !(1 << (cap % 32));
+        }
+        let res = unsafe { libc::syscall(libc::SYS_capset, &mut header, data.as_ptr()) };
+        assert_eq!(res, 0, "capset failed: {}", io::Error::last_os_error());
+        f()
+    })
+    .join()
+    .unwrap()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+struct CapHeader {
+    version: u32,
+    pid: libc::c_int,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct CapData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+/// Returns the capabilities of the calling thread.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn get_capabilities() -> (CapHeader, [CapData; 2]) {
+    const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+    let mut header = CapHeader {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapData {
+        effective: 0,
+        permitted: 0,
+        inheritable: 0,
+    }; 2];
+    let res = unsafe { libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) };
+    assert_eq!(res, 0, "capget failed: {}", io::Error::last_os_error());
+    (header, data)
+}
+
+/// Sockets supporting `SO_BINDTODEVICE` and `SO_MARK`.
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+pub trait DeviceAndMark: Send + 'static {
+    fn bind_device(&self, interface: &[u8]) -> io::Result<()>;
+    fn device(&self) -> io::Result<Option<Vec<u8>>>;
+    fn set_mark(&self, mark: u32) -> io::Result<()>;
+    fn mark(&self) -> io::Result<u32>;
+}
+
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+macro_rules! impl_device_and_mark {
+    ($type: ty, $device: ident, $mark: ident) => {
+        impl DeviceAndMark for $type {
+            fn bind_device(&self, interface: &[u8]) -> io::Result<()> {
+                <$type>::bind_device(self, interface)
+            }
+
+            fn device(&self) -> io::Result<Option<Vec<u8>>> {
+                <$type>::$device(self)
+            }
+
+            fn set_mark(&self, mark: u32) -> io::Result<()> {
+                <$type>::set_mark(self, mark)
+            }
+
+            fn mark(&self) -> io::Result<u32> {
+                <$type>::$mark(self)
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+impl_device_and_mark!(mio::net::TcpListener, device, mark);
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+impl_device_and_mark!(mio::net::TcpSocket, get_device, get_mark);
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+impl_device_and_mark!(mio::net::UdpSocket, device, mark);
+
+/// Checks `bind_device`, `device`, `set_mark` and `mark` on sockets returned
+/// by `new_socket`, both with and without the required capabilities.
+#[cfg(all(feature = "net", any(target_os = "android", target_os = "linux")))]
+pub fn assert_bind_device_and_mark<S, F>(new_socket: F)
+where
+    S: DeviceAndMark,
+    F: Fn() -> S + Clone + Send + 'static,
+{
+    if has_capability(CAP_NET_RAW) && has_capability(CAP_NET_ADMIN) {
+        let socket = new_socket();
+        socket.bind_device(b"lo").unwrap();
+        assert_eq!(socket.device().unwrap(), Some(b"lo".to_vec()));
+        socket.bind_device(b"").unwrap();
+        assert_eq!(socket.device().unwrap(), None);
+        let err = socket.bind_device(b"not-an-interface").unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENODEV));
+
+        socket.set_mark(42).unwrap();
+        assert_eq!(socket.mark().unwrap(), 42);
+    } else {
+        info!("skipping privileged part of test, missing CAP_NET_RAW or CAP_NET_ADMIN");
+    }
+
+    let new = new_socket.clone();
+    without_capabilities(&[CAP_NET_RAW], move || {
+        let socket = new();
+        // Binding requires `CAP_NET_RAW` before Linux 5.7, changing the
+        // binding always requires it.
+        let err = match socket.bind_device(b"lo") {
+            Ok(()) => socket.bind_device(b"").unwrap_err(),
+            Err(err) => err,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    });
+
+    // Since Linux 5.17 `CAP_NET_RAW` is also accepted for setting the mark.
+    without_capabilities(&[CAP_NET_ADMIN, CAP_NET_RAW], move || {
+        let err = new_socket().set_mark(42).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    });
+}
+
 /// Returns a path to a temporary file using `name` as filename.
 pub fn temp_file(name: &'static str) -> PathBuf {
     let mut path = temp_dir();